@@ -1,20 +1,56 @@
+use crate::dates::Calendar;
+use crate::number::Rational;
+use crate::types::Layout;
+
 pub enum Statement {
     Assign(String, Term),
+    /// `tax(x) = x * 20%`
+    Define(String, Vec<String>, Term),
+    /// `1 sprint = 2 weeks`, or `unit sprint = 2 weeks` for a single one
+    DefineUnit(String, f64, Term),
     Transform(Term, Term),
     Basic(Term),
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum Term {
     Binary(Box<Term>, Op, Box<Term>),
     Negate(Box<Term>),
+    /// `0.25 as %`
+    AsPercent(Box<Term>),
     Ident(String),
-    Num(f64),
+    /// `round($12.345, 2)`
+    Call(String, Vec<Term>),
+    Num(Rational),
+    Percent(Rational),
+    /// `2026-10-18` or `3pm`, in seconds like `Value::moment`
+    Moment(Calendar, i64),
+    /// `next friday` is `Weekday(1, 4)` and `last monday` is `Weekday(-1, 0)`
+    Weekday(i64, u32),
+    /// `9am London`
+    Zoned(Box<Term>, String),
+    /// The target of `now in PST`, which is a unit if converting anything
+    /// but a date or time
+    Zone(String),
+    /// `5 ft 11 in` or `1h 30min`, summed in the last unit
+    Compound(Vec<Term>),
+    /// The target of `5400 s in hours and minutes` or `in h:m:s`
+    Mixed(Vec<String>, Layout),
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum Op {
     Add,
     Sub,
     Mul,
     Div,
     Pow,
+    /// `5% of £2000`
+    Of,
+    /// `20% off £300`
+    Off,
+    /// `15% on £300`
+    On,
+    /// `£50 as a % of £200`
+    AsPercentOf,
 }
\ No newline at end of file
@@ -0,0 +1,475 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+/// An integer of unbounded size
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    /// Base 2^32 digits, least significant first, with no leading zeros
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn new(negative: bool, mut magnitude: Vec<u32>) -> BigInt {
+        trim(&mut magnitude);
+        BigInt {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    /// Digits in the given base, e.g. `ff` in base 16, with an optional `-`
+    pub fn from_str_radix(s: &str, radix: u32) -> Option<BigInt> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+
+        let base = BigInt::from(radix as i64);
+        let magnitude = digits.chars().fold(BigInt::zero(), |acc, digit| {
+            &(&acc * &base) + &BigInt::from(digit.to_digit(radix).unwrap() as i64)
+        });
+        Some(BigInt::new(negative, magnitude.magnitude))
+    }
+
+    pub fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    pub fn one() -> BigInt {
+        BigInt::from(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn abs(&self) -> BigInt {
+        BigInt::new(false, self.magnitude.clone())
+    }
+
+    /// How many bits the magnitude takes
+    pub fn bits(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// Division rounding towards zero, like `/` and `%` on primitives.
+    /// Panics if `divisor` is zero.
+    pub fn div_rem(&self, divisor: &BigInt) -> (BigInt, BigInt) {
+        assert!(!divisor.is_zero(), "division by zero");
+
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+        (
+            BigInt::new(self.negative != divisor.negative, quotient),
+            BigInt::new(self.negative, remainder),
+        )
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut power = BigInt::one();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                power = &power * &base;
+            }
+            exponent >>= 1;
+            // the last square would go unused, and is the biggest
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+        power
+    }
+
+    /// The exact `n`th root, if there is one. Only looked for among
+    /// numbers a float can estimate, which is plenty for a document.
+    pub fn root(&self, n: u32) -> Option<BigInt> {
+        if self.negative || n == 0 {
+            return None;
+        }
+
+        let estimate = self.to_f64().powf(1.0 / n as f64).round();
+        if !estimate.is_finite() || estimate >= 2f64.powi(53) {
+            return None;
+        }
+
+        let estimate = estimate as i64;
+        (estimate - 1..=estimate + 1)
+            .map(BigInt::from)
+            .find(|root| !root.is_negative() && root.pow(n) == *self)
+    }
+
+    /// The greatest common divisor, always positive unless both are zero.
+    /// Lehmer's algorithm: most steps of Euclid's are worked out on the
+    /// top 63 bits alone and then applied to the whole numbers at once.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let (mut a, mut b) = (self.abs(), other.abs());
+        if a < b {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        while b.bits() > 63 {
+            let shift = a.bits() - 63;
+            let (mut x, mut y) = (top_bits(&a.magnitude, shift), top_bits(&b.magnitude, shift));
+
+            // a = ca * a + cb * b and b = cc * a + cd * b, for as long as the
+            // quotient is the same whichever way the lower bits go
+            let (mut ca, mut cb, mut cc, mut cd) = (1i128, 0i128, 0i128, 1i128);
+            while y + cc != 0 && y + cd != 0 {
+                let quotient = (x + ca) / (y + cc);
+                if quotient != (x + cb) / (y + cd) {
+                    break;
+                }
+                (ca, cc) = (cc, ca - quotient * cc);
+                (cb, cd) = (cd, cb - quotient * cd);
+                (x, y) = (y, x - quotient * y);
+            }
+
+            if cb == 0 {
+                let remainder = a.div_rem(&b).1;
+                a = b;
+                b = remainder;
+            } else {
+                let (x, y) = (&a.magnitude, &b.magnitude);
+                let combined = (
+                    combine_magnitude(x, ca as i64, y, cb as i64),
+                    combine_magnitude(x, cc as i64, y, cd as i64),
+                );
+                a = BigInt::new(false, combined.0);
+                b = BigInt::new(false, combined.1);
+            }
+        }
+
+        // `b` is small now, so this takes a step or two
+        while !b.is_zero() {
+            let remainder = a.div_rem(&b).1;
+            a = b;
+            b = remainder;
+        }
+        a
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.bits() > 63 {
+            return None;
+        }
+
+        let magnitude =
+            self.magnitude.iter().rev().fold(0i64, |acc, &digit| (acc << 32) | digit as i64);
+        Some(if self.negative { -magnitude } else { magnitude })
+    }
+
+    /// The closest `f64`, infinite if it is too big
+    pub fn to_f64(&self) -> f64 {
+        let bits = self.bits();
+        let magnitude = if bits <= 64 {
+            self.magnitude.iter().rev().fold(0u64, |acc, &digit| (acc << 32) | digit as u64) as f64
+        } else {
+            // the top 64 bits are more than a `f64` can hold anyway
+            let shift = bits - 64;
+            let top = shift_right(&self.magnitude, shift);
+            let top = top.iter().rev().fold(0u64, |acc, &digit| (acc << 32) | digit as u64);
+            top as f64 * 2f64.powi(shift.min(i32::MAX as u64) as i32)
+        };
+
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let digit = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(digit as u32);
+        carry = digit >> 32;
+    }
+    sum.push(carry as u32);
+    trim(&mut sum);
+    sum
+}
+
+/// `a - b`, where `a >= b`
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &digit) in a.iter().enumerate() {
+        let mut digit = digit as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if digit < 0 {
+            digit += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(digit as u32);
+    }
+    trim(&mut difference);
+    difference
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let digit = product[i + j] as u64 + x as u64 * y as u64 + carry;
+            product[i + j] = digit as u32;
+            carry = digit >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    trim(&mut product);
+    product
+}
+
+fn shift_right(magnitude: &[u32], shift: u64) -> Vec<u32> {
+    let (digits, bits) = ((shift / 32) as usize, (shift % 32) as u32);
+    let mut shifted: Vec<u32> = magnitude.iter().skip(digits).copied().collect();
+    if bits > 0 {
+        for i in 0..shifted.len() {
+            let high = shifted.get(i + 1).map_or(0, |&next| next << (32 - bits));
+            shifted[i] = (shifted[i] >> bits) | high;
+        }
+    }
+    trim(&mut shifted);
+    shifted
+}
+
+/// `ca * a + cb * b`, where the result is known not to be negative
+fn combine_magnitude(a: &[u32], ca: i64, b: &[u32], cb: i64) -> Vec<u32> {
+    let mut combined = Vec::with_capacity(a.len().max(b.len()) + 2);
+    let mut carry = 0i128;
+    for i in 0..a.len().max(b.len()) {
+        let (x, y) = (*a.get(i).unwrap_or(&0) as i64, *b.get(i).unwrap_or(&0) as i64);
+        carry += ca as i128 * x as i128 + cb as i128 * y as i128;
+        combined.push(carry as u32);
+        carry >>= 32;
+    }
+    combined.extend([carry as u32, (carry >> 32) as u32]);
+    combined
+}
+
+/// The 63 bits of `magnitude` from bit `shift` up, which must be all of them
+fn top_bits(magnitude: &[u32], shift: u64) -> i128 {
+    let (digit, bits) = ((shift / 32) as usize, shift % 32);
+    let word = |i: usize| *magnitude.get(i).unwrap_or(&0) as u128;
+    let top = word(digit) | word(digit + 1) << 32 | word(digit + 2) << 64;
+    (top >> bits) as i128
+}
+
+fn shift_left(magnitude: &[u32], bits: u32) -> Vec<u32> {
+    let mut shifted = Vec::with_capacity(magnitude.len() + 1);
+    let mut carry = 0;
+    for &digit in magnitude {
+        shifted.push((digit << bits) | carry);
+        carry = if bits > 0 { digit >> (32 - bits) } else { 0 };
+    }
+    shifted.push(carry);
+    shifted
+}
+
+/// Long division a whole digit at a time (Knuth's algorithm D), with the
+/// divisor shifted so its top digit gives a good estimate of each digit of
+/// the quotient
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+
+    if let [divisor] = *b {
+        let mut quotient = vec![0u32; a.len()];
+        let mut remainder = 0u64;
+        for (i, &digit) in a.iter().enumerate().rev() {
+            let current = (remainder << 32) | digit as u64;
+            quotient[i] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        trim(&mut quotient);
+        let mut remainder = vec![remainder as u32];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+
+    let shift = b.last().unwrap().leading_zeros();
+    let b = shift_left(b, shift);
+    let b = &b[..b.len() - 1];
+    let mut a = shift_left(a, shift);
+    let (n, m) = (b.len(), a.len() - b.len());
+    let (top, next) = (b[n - 1] as u64, b[n - 2] as u64);
+
+    let mut quotient = vec![0u32; m];
+    for j in (0..m).rev() {
+        // estimate from the top two digits, which is at most 2 too big
+        let current = ((a[j + n] as u64) << 32) | a[j + n - 1] as u64;
+        let mut estimate = current / top;
+        let mut rest = current % top;
+        while estimate >> 32 != 0 || estimate * next > (rest << 32) | a[j + n - 2] as u64 {
+            estimate -= 1;
+            rest += top;
+            if rest >> 32 != 0 {
+                break;
+            }
+        }
+
+        // a -= estimate * b, shifted to digit j
+        let (mut carry, mut borrow) = (0u64, 0i64);
+        for i in 0..n {
+            let product = estimate * b[i] as u64 + carry;
+            carry = product >> 32;
+            let digit = a[i + j] as i64 - borrow - (product & 0xffff_ffff) as i64;
+            a[i + j] = digit as u32;
+            borrow = (digit < 0) as i64;
+        }
+        let digit = a[j + n] as i64 - borrow - carry as i64;
+        a[j + n] = digit as u32;
+
+        // the rare estimate that was still 1 too big
+        if digit < 0 {
+            estimate -= 1;
+            let mut carry = 0u64;
+            for i in 0..n {
+                let sum = a[i + j] as u64 + b[i] as u64 + carry;
+                a[i + j] = sum as u32;
+                carry = sum >> 32;
+            }
+            a[j + n] = a[j + n].wrapping_add(carry as u32);
+        }
+        quotient[j] = estimate as u32;
+    }
+
+    trim(&mut quotient);
+    (quotient, shift_right(&a[..n], shift as u64))
+}
+
+impl From<i64> for BigInt {
+    fn from(num: i64) -> Self {
+        let magnitude = num.unsigned_abs();
+        BigInt::new(num < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::new(!self.negative, self.magnitude)
+    }
+}
+
+impl<'a> Add<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitude(&self.magnitude, &other.magnitude));
+        }
+
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => {
+                BigInt::new(other.negative, sub_magnitude(&other.magnitude, &self.magnitude))
+            }
+            _ => BigInt::new(self.negative, sub_magnitude(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl<'a> Sub<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other.clone()
+    }
+}
+
+impl<'a> Mul<&'a BigInt> for &'a BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = ();
+
+    /// Decimal digits with an optional `-`
+    fn from_str(s: &str) -> Result<BigInt, ()> {
+        BigInt::from_str_radix(s, 10).ok_or(())
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // peel off nine decimal digits at a time, dividing in place by a
+        // constant, which compiles to multiplications
+        const BILLION: u64 = 1_000_000_000;
+        let mut chunks = Vec::new();
+        let mut rest = self.magnitude.clone();
+        while !rest.is_empty() {
+            let mut remainder = 0u64;
+            for digit in rest.iter_mut().rev() {
+                let current = (remainder << 32) | *digit as u64;
+                *digit = (current / BILLION) as u32;
+                remainder = current % BILLION;
+            }
+            chunks.push(remainder as u32);
+            trim(&mut rest);
+        }
+
+        let sign = if self.negative { "-" } else { "" };
+        write!(f, "{}{}", sign, chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
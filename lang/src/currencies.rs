@@ -0,0 +1,203 @@
+/// An ISO 4217 currency
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Currency {
+    pub code: &'static str,
+    pub name: &'static str,
+    /// Digits after the decimal point, e.g. 2 for cents and 0 for yen
+    pub minor_units: i32,
+    /// Symbols shared by several currencies, like `$` or `kr`, belong to
+    /// the most traded one
+    pub symbols: &'static [&'static str],
+}
+
+const fn currency(
+    code: &'static str,
+    name: &'static str,
+    minor_units: i32,
+    symbols: &'static [&'static str],
+) -> Currency {
+    Currency {
+        code,
+        name,
+        minor_units,
+        symbols,
+    }
+}
+
+/// Every active ISO 4217 currency
+pub const CURRENCIES: [Currency; 157] = [
+    currency("AED", "UAE dirham", 2, &[]),
+    currency("AFN", "Afghan afghani", 2, &[]),
+    currency("ALL", "Albanian lek", 2, &[]),
+    currency("AMD", "Armenian dram", 2, &[]),
+    currency("ANG", "Netherlands Antillean guilder", 2, &[]),
+    currency("AOA", "Angolan kwanza", 2, &["Kz"]),
+    currency("ARS", "Argentine peso", 2, &[]),
+    currency("AUD", "Australian dollar", 2, &["A$"]),
+    currency("AWG", "Aruban florin", 2, &[]),
+    currency("AZN", "Azerbaijani manat", 2, &["₼"]),
+    currency("BAM", "Bosnia and Herzegovina convertible mark", 2, &[]),
+    currency("BBD", "Barbados dollar", 2, &[]),
+    currency("BDT", "Bangladeshi taka", 2, &[]),
+    currency("BGN", "Bulgarian lev", 2, &[]),
+    currency("BHD", "Bahraini dinar", 3, &[]),
+    currency("BIF", "Burundian franc", 0, &[]),
+    currency("BMD", "Bermudian dollar", 2, &[]),
+    currency("BND", "Brunei dollar", 2, &[]),
+    currency("BOB", "Boliviano", 2, &["Bs"]),
+    currency("BRL", "Brazilian real", 2, &["R$"]),
+    currency("BSD", "Bahamian dollar", 2, &[]),
+    currency("BTN", "Bhutanese ngultrum", 2, &[]),
+    currency("BWP", "Botswana pula", 2, &[]),
+    currency("BYN", "Belarusian ruble", 2, &[]),
+    currency("BZD", "Belize dollar", 2, &[]),
+    currency("CAD", "Canadian dollar", 2, &["CA$"]),
+    currency("CDF", "Congolese franc", 2, &[]),
+    currency("CHF", "Swiss franc", 2, &[]),
+    currency("CLP", "Chilean peso", 0, &[]),
+    currency("CNY", "Renminbi", 2, &["CN¥"]),
+    currency("COP", "Colombian peso", 2, &[]),
+    currency("CRC", "Costa Rican colon", 2, &["₡"]),
+    currency("CUP", "Cuban peso", 2, &[]),
+    currency("CVE", "Cape Verdean escudo", 2, &[]),
+    currency("CZK", "Czech koruna", 2, &["Kč"]),
+    currency("DJF", "Djiboutian franc", 0, &[]),
+    currency("DKK", "Danish krone", 2, &[]),
+    currency("DOP", "Dominican peso", 2, &["RD$"]),
+    currency("DZD", "Algerian dinar", 2, &[]),
+    currency("EGP", "Egyptian pound", 2, &["E£"]),
+    currency("ERN", "Eritrean nakfa", 2, &[]),
+    currency("ETB", "Ethiopian birr", 2, &[]),
+    currency("EUR", "Euro", 2, &["€"]),
+    currency("FJD", "Fiji dollar", 2, &[]),
+    currency("FKP", "Falkland Islands pound", 2, &[]),
+    currency("GBP", "Pound sterling", 2, &["£"]),
+    currency("GEL", "Georgian lari", 2, &["₾"]),
+    currency("GHS", "Ghanaian cedi", 2, &["₵"]),
+    currency("GIP", "Gibraltar pound", 2, &[]),
+    currency("GMD", "Gambian dalasi", 2, &[]),
+    currency("GNF", "Guinean franc", 0, &[]),
+    currency("GTQ", "Guatemalan quetzal", 2, &[]),
+    currency("GYD", "Guyanese dollar", 2, &[]),
+    currency("HKD", "Hong Kong dollar", 2, &["HK$"]),
+    currency("HNL", "Honduran lempira", 2, &[]),
+    currency("HTG", "Haitian gourde", 2, &[]),
+    currency("HUF", "Hungarian forint", 2, &[]),
+    currency("IDR", "Indonesian rupiah", 2, &["Rp"]),
+    currency("ILS", "Israeli new shekel", 2, &["₪"]),
+    currency("INR", "Indian rupee", 2, &["₹"]),
+    currency("IQD", "Iraqi dinar", 3, &[]),
+    currency("IRR", "Iranian rial", 2, &[]),
+    currency("ISK", "Icelandic krona", 0, &[]),
+    currency("JMD", "Jamaican dollar", 2, &["J$"]),
+    currency("JOD", "Jordanian dinar", 3, &[]),
+    currency("JPY", "Japanese yen", 0, &["¥"]),
+    currency("KES", "Kenyan shilling", 2, &["KSh"]),
+    currency("KGS", "Kyrgyzstani som", 2, &[]),
+    currency("KHR", "Cambodian riel", 2, &[]),
+    currency("KMF", "Comoro franc", 0, &[]),
+    currency("KPW", "North Korean won", 2, &[]),
+    currency("KRW", "South Korean won", 0, &["₩"]),
+    currency("KWD", "Kuwaiti dinar", 3, &[]),
+    currency("KYD", "Cayman Islands dollar", 2, &[]),
+    currency("KZT", "Kazakhstani tenge", 2, &["₸"]),
+    currency("LAK", "Lao kip", 2, &["₭"]),
+    currency("LBP", "Lebanese pound", 2, &[]),
+    currency("LKR", "Sri Lankan rupee", 2, &[]),
+    currency("LRD", "Liberian dollar", 2, &[]),
+    currency("LSL", "Lesotho loti", 2, &[]),
+    currency("LYD", "Libyan dinar", 3, &[]),
+    currency("MAD", "Moroccan dirham", 2, &[]),
+    currency("MDL", "Moldovan leu", 2, &[]),
+    currency("MGA", "Malagasy ariary", 2, &[]),
+    currency("MKD", "Macedonian denar", 2, &[]),
+    currency("MMK", "Myanmar kyat", 2, &[]),
+    currency("MNT", "Mongolian togrog", 2, &["₮"]),
+    currency("MOP", "Macanese pataca", 2, &[]),
+    currency("MRU", "Mauritanian ouguiya", 2, &[]),
+    currency("MUR", "Mauritian rupee", 2, &[]),
+    currency("MVR", "Maldivian rufiyaa", 2, &[]),
+    currency("MWK", "Malawian kwacha", 2, &[]),
+    currency("MXN", "Mexican peso", 2, &["MX$"]),
+    currency("MYR", "Malaysian ringgit", 2, &["RM"]),
+    currency("MZN", "Mozambican metical", 2, &[]),
+    currency("NAD", "Namibian dollar", 2, &[]),
+    currency("NGN", "Nigerian naira", 2, &["₦"]),
+    currency("NIO", "Nicaraguan cordoba", 2, &[]),
+    currency("NOK", "Norwegian krone", 2, &[]),
+    currency("NPR", "Nepalese rupee", 2, &[]),
+    currency("NZD", "New Zealand dollar", 2, &["NZ$"]),
+    currency("OMR", "Omani rial", 3, &[]),
+    currency("PAB", "Panamanian balboa", 2, &[]),
+    currency("PEN", "Peruvian sol", 2, &[]),
+    currency("PGK", "Papua New Guinean kina", 2, &[]),
+    currency("PHP", "Philippine peso", 2, &["₱"]),
+    currency("PKR", "Pakistani rupee", 2, &["₨"]),
+    currency("PLN", "Polish zloty", 2, &["zł"]),
+    currency("PYG", "Paraguayan guarani", 0, &["₲"]),
+    currency("QAR", "Qatari riyal", 2, &[]),
+    currency("RON", "Romanian leu", 2, &["lei"]),
+    currency("RSD", "Serbian dinar", 2, &[]),
+    currency("RUB", "Russian ruble", 2, &["₽"]),
+    currency("RWF", "Rwandan franc", 0, &[]),
+    currency("SAR", "Saudi riyal", 2, &[]),
+    currency("SBD", "Solomon Islands dollar", 2, &[]),
+    currency("SCR", "Seychelles rupee", 2, &[]),
+    currency("SDG", "Sudanese pound", 2, &[]),
+    currency("SEK", "Swedish krona", 2, &["kr"]),
+    currency("SGD", "Singapore dollar", 2, &["S$"]),
+    currency("SHP", "Saint Helena pound", 2, &[]),
+    currency("SLE", "Sierra Leonean leone", 2, &[]),
+    currency("SOS", "Somali shilling", 2, &[]),
+    currency("SRD", "Surinamese dollar", 2, &[]),
+    currency("SSP", "South Sudanese pound", 2, &[]),
+    currency("STN", "Sao Tome and Principe dobra", 2, &[]),
+    currency("SVC", "Salvadoran colon", 2, &[]),
+    currency("SYP", "Syrian pound", 2, &[]),
+    currency("SZL", "Swazi lilangeni", 2, &[]),
+    currency("THB", "Thai baht", 2, &["฿"]),
+    currency("TJS", "Tajikistani somoni", 2, &[]),
+    currency("TMT", "Turkmenistan manat", 2, &[]),
+    currency("TND", "Tunisian dinar", 3, &[]),
+    currency("TOP", "Tongan paanga", 2, &[]),
+    currency("TRY", "Turkish lira", 2, &["₺"]),
+    currency("TTD", "Trinidad and Tobago dollar", 2, &["TT$"]),
+    currency("TWD", "New Taiwan dollar", 2, &["NT$"]),
+    currency("TZS", "Tanzanian shilling", 2, &[]),
+    currency("UAH", "Ukrainian hryvnia", 2, &["₴"]),
+    currency("UGX", "Ugandan shilling", 0, &[]),
+    currency("USD", "United States dollar", 2, &["$", "US$"]),
+    currency("UYU", "Uruguayan peso", 2, &[]),
+    currency("UYW", "Unidad previsional", 4, &[]),
+    currency("UZS", "Uzbekistan sum", 2, &[]),
+    currency("VED", "Venezuelan digital bolivar", 2, &[]),
+    currency("VES", "Venezuelan sovereign bolivar", 2, &[]),
+    currency("VND", "Vietnamese dong", 0, &["₫"]),
+    currency("VUV", "Vanuatu vatu", 0, &[]),
+    currency("WST", "Samoan tala", 2, &[]),
+    currency("XAF", "CFA franc BEAC", 0, &[]),
+    currency("XCD", "East Caribbean dollar", 2, &[]),
+    currency("XOF", "CFA franc BCEAO", 0, &[]),
+    currency("XPF", "CFP franc", 0, &[]),
+    currency("YER", "Yemeni rial", 2, &[]),
+    currency("ZAR", "South African rand", 2, &[]),
+    currency("ZMW", "Zambian kwacha", 2, &[]),
+    currency("ZWG", "Zimbabwe gold", 2, &[]),
+];
+
+/// Codes also understood in lower case, e.g. `usd`. Others, like `ALL` or
+/// `TOP`, are too often ordinary words.
+pub const LOWER_CASE_CODES: [&str; 20] = [
+    "AUD", "BRL", "CAD", "CHF", "CNY", "CZK", "DKK", "EUR", "GBP", "HKD", "INR", "JPY", "MXN",
+    "NOK", "NZD", "PLN", "RON", "RUB", "SEK", "USD",
+];
+
+/// The currency with the given ISO 4217 code, e.g. `JPY`
+pub fn find(code: &str) -> Option<&'static Currency> {
+    CURRENCIES.iter().find(|currency| currency.code == code)
+}
+
+/// The currency a symbol like `£` or `kr` stands for
+pub fn by_symbol(symbol: &str) -> Option<&'static Currency> {
+    CURRENCIES.iter().find(|currency| currency.symbols.contains(&symbol))
+}
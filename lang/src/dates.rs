@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Dates can be written, and moved, from year 0 up to this one
+pub const MAX_YEAR: i64 = 9999;
+
+/// The days of the week, Monday first
+pub const WEEKDAYS: [&str; 7] =
+    ["monday", "tuesday", "wednesday", "thursday", "friday", "saturday", "sunday"];
+
+/// Which points in time a value stands for. Dates and datetimes are kept
+/// as wall clock seconds since 1970-01-01 00:00, and times of day as
+/// seconds since midnight.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Calendar {
+    /// `2026-10-18`
+    Date,
+    /// `3pm` or `14:30`
+    Time,
+    /// `2026-10-18 14:30`
+    DateTime,
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar,
+/// using Howard Hinnant's `days_from_civil`
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The year, month and day `days` after 1970-01-01
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+pub fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a valid date, e.g. not `2026-02-30`
+pub fn date(year: i64, month: u32, day: u32) -> Option<i64> {
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day))
+}
+
+/// The day of the week of a day since 1970-01-01, 0 being Monday
+pub fn weekday(days: i64) -> u32 {
+    // 1970-01-01 was a Thursday
+    (days + 3).rem_euclid(7) as u32
+}
+
+/// The same day `months` later, or the last day of that month if it is
+/// shorter, so a month after January 31st is the end of February. `None`
+/// past `MAX_YEAR` or before year 0.
+pub fn add_months(days: i64, months: i64) -> Option<i64> {
+    let (year, month, day) = civil_from_days(days);
+    let index = (year * 12 + month as i64 - 1).checked_add(months)?;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+    if !(0..=MAX_YEAR).contains(&year) {
+        return None;
+    }
+
+    Some(days_from_civil(year, month, day.min(days_in_month(year, month))))
+}
+
+/// Wall clock seconds as a whole number, if they fall between year 0 and
+/// `MAX_YEAR`
+pub fn whole_seconds(seconds: f64) -> Option<i64> {
+    // about 12,000 years either side of 1970, so the cast can't saturate
+    if seconds.is_nan() || seconds.abs() >= 4e11 {
+        return None;
+    }
+
+    let seconds = seconds.floor() as i64;
+    let (year, _, _) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    (0..=MAX_YEAR).contains(&year).then_some(seconds)
+}
+
+/// The time in UTC, as seconds since 1970-01-01 00:00
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> i64 {
+    (js_sys::Date::now() / 1000.0).floor() as i64
+}
+
+/// The time in UTC, as seconds since 1970-01-01 00:00
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs() as i64),
+    }
+}
+
+/// How far ahead of UTC the browser's clock is at a UTC time, daylight
+/// saving included
+#[cfg(target_arch = "wasm32")]
+pub fn local_offset(utc: i64) -> i64 {
+    let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(utc as f64 * 1000.0));
+    (date.get_timezone_offset() * -60.0) as i64
+}
+
+/// How far ahead of UTC the local clock is. Without the host's time zone
+/// settings the local clock is UTC, see `Environment::set_time_zone`.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn local_offset(_utc: i64) -> i64 {
+    0
+}
+
+/// Renders seconds like `2026-10-18`, `14:30` or `2026-10-18 14:30:05`
+pub fn format(calendar: Calendar, seconds: i64) -> String {
+    let (days, time) = (seconds.div_euclid(SECONDS_PER_DAY), seconds.rem_euclid(SECONDS_PER_DAY));
+    let (year, month, day) = civil_from_days(days);
+    let date = format!("{:04}-{:02}-{:02}", year, month, day);
+    let time = match (time / 3600, time % 3600 / 60, time % 60) {
+        (hours, minutes, 0) => format!("{:02}:{:02}", hours, minutes),
+        (hours, minutes, seconds) => format!("{:02}:{:02}:{:02}", hours, minutes, seconds),
+    };
+
+    match calendar {
+        Calendar::Date => date,
+        Calendar::Time => time,
+        Calendar::DateTime => format!("{} {}", date, time),
+    }
+}
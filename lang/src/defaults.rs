@@ -1,42 +1,239 @@
+use super::currencies::{CURRENCIES, LOWER_CASE_CODES};
+use super::rates::StaticRates;
 use super::types::*;
+use super::units::Prefixes;
+use std::sync::Arc;
+
+/// The rates used when the host doesn't provide any, rough figures for
+/// the most traded currencies; see `parser::parse_with_rates`
+fn fallback_rates() -> StaticRates {
+    StaticRates::new("GBP")
+        .with_rate("EUR", 1.06)
+        .with_rate("RON", 5.07)
+        .with_rate("USD", 1.20)
+        .with_rate("JPY", 190.0)
+        .with_rate("CHF", 1.12)
+        .with_rate("CNY", 8.7)
+        .with_rate("INR", 100.0)
+        .with_rate("RUB", 95.0)
+        .with_rate("PLN", 5.0)
+        .with_rate("SEK", 13.4)
+        .with_rate("NOK", 13.5)
+        .with_rate("DKK", 8.6)
+        .with_rate("CAD", 1.65)
+        .with_rate("AUD", 1.85)
+}
 
 lazy_static! {
-    pub static ref ENVIRONMENT: Environment = environment![
-       "gbp" to "£" is 1,
-       "usd" to "$" is 1,
-       "eur" to "€" is 1,
-       "gbp" to "eur" is 1.06,
-       "gbp" to "ron" is 5.07,
-       "gbp" to "usd" is 1.20,
-       "km" to "m" is 1000,
-       "m" to "dm" is 10,
-       "dm" to "cm" is 10,
-       "cm" to "mm" is 10,
-       "kg" to "g" is 1000,
-       "g" to "mg" is 1000,
-       "minute" to "second" is 60,
-       "hour" to "minute" is 60,
-       "day" to "hour" is 24,
-       "week" to "day" is 7,
-       "month" to "day" is 30.5,
-       "year" to "month" is 12,
-       "minute" to "minutes" is 1,
-       "hour" to "hours" is 1,
-       "day" to "days" is 1,
-       "week" to "weeks" is 1,
-       "month" to "months" is 1,
-       "year" to "years" is 1,
-       "sec" to "second" is 1,
-       "min" to "minute" is 1,
-       "s" to "second" is 1,
-       // m is for meters, not minutes!
-       "h" to "hour" is 1,
-       "d" to "day" is 1,
-       "w" to "week" is 1,
-       "y" to "year" is 1,
-       "ron" to "RON" is 1,
-       "gbp" to "GBP" is 1,
-       "usd" to "USD" is 1,
-       "eur" to "EUR" is 1
-    ];
+    pub static ref ENVIRONMENT: Environment = {
+        let mut env = environment![
+            Length: "m" [Si] {
+                // imperial and US customary, as defined by the 1959
+                // international yard and pound agreement
+                "in" is 0.0254,
+                "ft" is 0.3048,
+                "yd" is 0.9144,
+                "mi" is 1609.344,
+                "thou" is 0.0000254,
+                "hand" is 0.1016,
+                "chain" is 20.1168,
+                "furlong" is 201.168,
+                "league" is 4828.032,
+                // nautical
+                "fathom" is 1.8288,
+                "cable" is 185.2,
+                "nmi" is 1852
+            },
+            Mass: "kg" {
+                "g" [Si] is 0.001,
+                "gr" is 0.00006479891,
+                "dr" is 0.0017718451953125,
+                "oz" is 0.028349523125,
+                "lb" is 0.45359237,
+                "st" is 6.35029318,
+                "ozt" is 0.0311034768,
+                // the US short ton and the imperial long ton
+                "ton" is 907.18474,
+                "long ton" is 1016.0469088
+            },
+            Time: "second" [Si] {
+                "minute" is 60,
+                "hour" is 3600,
+                "day" is 86400,
+                "week" is 604800,
+                // the averages of the Gregorian calendar, 365.2425 days a
+                // year; dates move by calendar months and years instead
+                "month" is 2629746,
+                "year" is 31556952
+            },
+            Temperature: "K" [Si] {
+                // 0 °C is 273.15 K
+                "°C" offset 273.15 is 1,
+                // 0 °F is -17.78 °C
+                "°F" offset (459.67 * 5.0 / 9.0) is 5.0 / 9.0
+            },
+            Angle: "rad" [Si] {
+                "degree" is std::f64::consts::PI / 180.0
+            },
+            Data: "B" [SiAndBinary] {
+                "bit" [SiAndBinary] is 0.125
+            }
+        ];
+
+        let table = &mut env.units;
+
+        // symbols and abbreviations of the units above, case sensitive
+        let aliases = [
+            ("s", "second"),
+            ("sec", "second"),
+            // m is for meters, not minutes!
+            ("min", "minute"),
+            ("h", "hour"),
+            ("d", "day"),
+            ("w", "week"),
+            ("y", "year"),
+            ("degC", "°C"),
+            ("degF", "°F"),
+            ("deg", "degree"),
+            ("°", "degree"),
+        ];
+        for (alias, unit) in aliases.iter() {
+            table.define_alias(*alias, *unit);
+        }
+
+        // derived units, sized in terms of the ones above
+        table.define_derived("N", 1.0, &units!("kg" to 1, "m" to 1, "s" to -2), Prefixes::Si);
+        table.define_derived("J", 1.0, &units!("N" to 1, "m" to 1), Prefixes::Si);
+        table.define_derived("W", 1.0, &units!("J" to 1, "s" to -1), Prefixes::Si);
+        table.define_derived("Pa", 1.0, &units!("N" to 1, "m" to -2), Prefixes::Si);
+        table.define_derived("Hz", 1.0, &units!("s" to -1), Prefixes::Si);
+        table.define_derived("L", 0.001, &units!("m" to 3), Prefixes::Si);
+
+        // imperial, US customary and nautical units made of the ones above,
+        // sized in canonical units so their exact factors stay exact;
+        // volumes are US customary ones unless they say they are imperial
+        let volumes = [
+            ("gal", 0.003785411784),
+            ("qt", 0.000946352946),
+            ("pt", 0.000473176473),
+            ("cup", 0.0002365882365),
+            ("floz", 0.0000295735295625),
+            ("tbsp", 0.00001478676478125),
+            ("tsp", 0.00000492892159375),
+            ("bbl", 0.158987294928),
+            ("imperial gallon", 0.00454609),
+            ("imperial quart", 0.0011365225),
+            ("imperial pint", 0.00056826125),
+            ("imperial fluid ounce", 0.0000284130625),
+        ];
+        for (unit, cubic_metres) in volumes.iter() {
+            table.define_derived(*unit, *cubic_metres, &units!("m" to 3), Prefixes::None);
+        }
+        table.define_derived("acre", 4046.8564224, &units!("m" to 2), Prefixes::None);
+        table.define_derived("mph", 0.44704, &units!("m" to 1, "s" to -1), Prefixes::None);
+        table.define_ratio("kn", (1852, 3600), &units!("m" to 1, "s" to -1), Prefixes::None);
+        table.define_derived("lbf", 4.4482216152605, &units!("N" to 1), Prefixes::None);
+        table.define_derived("psi", 1.0, &units!("lbf" to 1, "in" to -2), Prefixes::None);
+        table.define_derived("BTU", 1055.05585262, &units!("J" to 1), Prefixes::None);
+        table.define_derived("hp", 745.69987158227, &units!("W" to 1), Prefixes::None);
+
+        // names in any case, as singular and plural, the preferred spelling
+        // of each unit first
+        let names = [
+            ("m", "metre", "metres"),
+            ("m", "meter", "meters"),
+            ("g", "gram", "grams"),
+            ("second", "second", "seconds"),
+            ("minute", "minute", "minutes"),
+            ("hour", "hour", "hours"),
+            ("day", "day", "days"),
+            ("week", "week", "weeks"),
+            ("month", "month", "months"),
+            ("year", "year", "years"),
+            ("K", "kelvin", "kelvins"),
+            ("°C", "celsius", "celsius"),
+            ("°F", "fahrenheit", "fahrenheit"),
+            ("rad", "radian", "radians"),
+            ("degree", "degree", "degrees"),
+            ("B", "byte", "bytes"),
+            ("bit", "bit", "bits"),
+            ("N", "newton", "newtons"),
+            ("J", "joule", "joules"),
+            ("W", "watt", "watts"),
+            ("Pa", "pascal", "pascals"),
+            ("Hz", "hertz", "hertz"),
+            ("L", "litre", "litres"),
+            ("L", "liter", "liters"),
+            ("in", "inch", "inches"),
+            ("ft", "foot", "feet"),
+            ("yd", "yard", "yards"),
+            ("mi", "mile", "miles"),
+            ("hand", "hand", "hands"),
+            ("chain", "chain", "chains"),
+            ("furlong", "furlong", "furlongs"),
+            ("league", "league", "leagues"),
+            ("fathom", "fathom", "fathoms"),
+            ("cable", "cable", "cables"),
+            ("nmi", "nautical mile", "nautical miles"),
+            ("gr", "grain", "grains"),
+            ("dr", "dram", "drams"),
+            ("oz", "ounce", "ounces"),
+            ("lb", "pound", "pounds"),
+            ("st", "stone", "stone"),
+            ("ozt", "troy ounce", "troy ounces"),
+            ("ton", "ton", "tons"),
+            ("ton", "short ton", "short tons"),
+            ("long ton", "long ton", "long tons"),
+            ("gal", "gallon", "gallons"),
+            ("qt", "quart", "quarts"),
+            ("pt", "pint", "pints"),
+            ("cup", "cup", "cups"),
+            ("floz", "fluid ounce", "fluid ounces"),
+            ("tbsp", "tablespoon", "tablespoons"),
+            ("tsp", "teaspoon", "teaspoons"),
+            ("bbl", "barrel", "barrels"),
+            ("imperial gallon", "imperial gallon", "imperial gallons"),
+            ("imperial quart", "imperial quart", "imperial quarts"),
+            ("imperial pint", "imperial pint", "imperial pints"),
+            ("imperial fluid ounce", "imperial fluid ounce", "imperial fluid ounces"),
+            ("acre", "acre", "acres"),
+            ("kn", "knot", "knots"),
+            ("lbf", "pound force", "pounds force"),
+            ("hp", "horsepower", "horsepower"),
+        ];
+        for (unit, singular, plural) in names.iter() {
+            table.define_name(*unit, singular, plural);
+        }
+        table.define_alias("l", "L");
+        for (alias, unit) in [("lbs", "lb"), ("fl oz", "floz"), ("kt", "kn")].iter() {
+            table.define_alias(*alias, *unit);
+        }
+
+        // results can be moved along these when picking the best unit
+        table.define_scale(vec!["mm", "cm", "m", "km"]);
+        table.define_scale(vec!["mg", "g", "kg"]);
+        table.define_scale(vec!["in", "ft", "mi"]);
+        table.define_scale(vec!["inches", "feet", "miles"]);
+        table.define_scale(vec!["oz", "lb"]);
+        table.define_scale(vec!["ounces", "pounds"]);
+        table.define_scale(vec!["floz", "cup", "qt", "gal"]);
+        table.define_scale(vec!["ms", "s", "min", "h", "d"]);
+        table.define_scale(vec!["second", "minute", "hour", "day"]);
+        table.define_scale(vec!["minutes", "hours", "days"]);
+        table.define_scale(vec!["B", "kB", "MB", "GB", "TB"]);
+
+        // currencies have no fixed size, they are sized by the rate provider
+        for currency in CURRENCIES.iter() {
+            table.define_currency(currency.code, currency.code);
+            for symbol in currency.symbols {
+                table.define_currency(*symbol, currency.code);
+            }
+        }
+        for code in LOWER_CASE_CODES.iter() {
+            table.define_currency(code.to_lowercase(), code);
+        }
+        env.set_rate_provider(Arc::new(fallback_rates()));
+
+        env
+    };
 }
@@ -0,0 +1,203 @@
+use peg::error::ParseError;
+use peg::str::LineCol;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::fmt;
+
+/// Everything that can go wrong while evaluating a single line
+#[derive(Debug, Clone, PartialEq)]
+pub enum DedoError {
+    /// The line is not valid syntax; `column` is 1-based
+    Parse {
+        column: usize,
+        expected: Vec<String>,
+    },
+    /// The line has nothing to evaluate, e.g. it is blank
+    EmptyLine,
+    UnknownUnit(String),
+    /// A unit definition like `1 m = 2 ft` names a unit that already exists
+    UnitAlreadyDefined(String),
+    /// There is no conversion from the first units to the second ones
+    IncompatibleUnits(String, String),
+    /// Units to split a value across go largest first, e.g. not `minutes
+    /// and hours`
+    UnitsOutOfOrder(String, String),
+    DivisionByZero,
+    /// `of`, `off` and `on` need a percentage on their left
+    NotAPercentage(String),
+    /// The units can only be raised to whole powers, e.g. `sqrt(2 m)`
+    FractionalUnitPower(String),
+    UnknownFunction(String),
+    /// A number like `1,250` that could be read as `1250` or `1.25`
+    AmbiguousSeparator(String),
+    /// A whole number was expected, e.g. `factorial(2.5)`
+    NotAWholeNumber(String),
+    /// A function was given an argument it isn't defined for, e.g.
+    /// `sqrt(-1)` or `ln(0)`
+    OutsideDomain {
+        function: String,
+        argument: String,
+    },
+    /// A function defined in the document ends up calling itself
+    RecursiveFunction(String),
+    WrongArgumentCount {
+        function: String,
+        min: usize,
+        max: Option<usize>,
+        got: usize,
+    },
+    /// A rates file could not be read or understood
+    InvalidRates(String),
+    /// The rate provider has no rate for the currency with this code
+    MissingRate(String),
+    /// `prev` was used with no value right above it
+    EmptyPrev,
+    /// `sum` was used with no values right above it
+    EmptySum,
+    /// `prod` was used with no values right above it
+    EmptyProd,
+    /// Only durations can be added to dates and times, e.g. not `today + 2 kg`
+    NotADuration(String),
+    /// An operation that makes no sense on dates and times, e.g. `today * 2`
+    DateArithmetic(String),
+    /// Not a zone of the IANA database, a city in one or an abbreviation
+    /// like `PST`, or a zone given to something that isn't a date or time
+    UnknownTimeZone(String),
+}
+
+impl DedoError {
+    /// A stable, machine-readable name for the error
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DedoError::Parse { .. } => "Parse",
+            DedoError::EmptyLine => "EmptyLine",
+            DedoError::UnknownUnit(_) => "UnknownUnit",
+            DedoError::UnitAlreadyDefined(_) => "UnitAlreadyDefined",
+            DedoError::IncompatibleUnits(_, _) => "IncompatibleUnits",
+            DedoError::UnitsOutOfOrder(_, _) => "UnitsOutOfOrder",
+            DedoError::DivisionByZero => "DivisionByZero",
+            DedoError::NotAPercentage(_) => "NotAPercentage",
+            DedoError::FractionalUnitPower(_) => "FractionalUnitPower",
+            DedoError::UnknownFunction(_) => "UnknownFunction",
+            DedoError::AmbiguousSeparator(_) => "AmbiguousSeparator",
+            DedoError::NotAWholeNumber(_) => "NotAWholeNumber",
+            DedoError::OutsideDomain { .. } => "OutsideDomain",
+            DedoError::RecursiveFunction(_) => "RecursiveFunction",
+            DedoError::WrongArgumentCount { .. } => "WrongArgumentCount",
+            DedoError::InvalidRates(_) => "InvalidRates",
+            DedoError::MissingRate(_) => "MissingRate",
+            DedoError::EmptyPrev => "EmptyPrev",
+            DedoError::EmptySum => "EmptySum",
+            DedoError::EmptyProd => "EmptyProd",
+            DedoError::NotADuration(_) => "NotADuration",
+            DedoError::DateArithmetic(_) => "DateArithmetic",
+            DedoError::UnknownTimeZone(_) => "UnknownTimeZone",
+        }
+    }
+}
+
+impl fmt::Display for DedoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DedoError::Parse { column, expected } => match expected.as_slice() {
+                [] => write!(f, "Unexpected input at column {}", column),
+                [token] => write!(f, "Unexpected input at column {}, expected {}", column, token),
+                tokens => write!(
+                    f,
+                    "Unexpected input at column {}, expected one of {}",
+                    column,
+                    tokens.join(", ")
+                ),
+            },
+            DedoError::EmptyLine => write!(f, "Nothing to evaluate"),
+            DedoError::UnknownUnit(unit) => write!(f, "Unknown unit `{}`", unit),
+            DedoError::UnitAlreadyDefined(unit) => write!(f, "`{}` is already a unit", unit),
+            DedoError::IncompatibleUnits(from, to) => write!(
+                f,
+                "Cannot convert {} to {}",
+                describe_units(from),
+                describe_units(to)
+            ),
+            DedoError::UnitsOutOfOrder(first, second) => {
+                write!(f, "Units go largest first, so `{}` can't follow `{}`", second, first)
+            }
+            DedoError::DivisionByZero => write!(f, "Division by zero"),
+            DedoError::NotAPercentage(units) => {
+                write!(f, "Expected a percentage, got {}", describe_units(units))
+            }
+            DedoError::FractionalUnitPower(units) => {
+                write!(f, "`{}` cannot be raised to a fractional power", units)
+            }
+            DedoError::UnknownFunction(name) => write!(f, "Unknown function `{}`", name),
+            DedoError::AmbiguousSeparator(num) => write!(
+                f,
+                "`{}` could mean {} or {}",
+                num,
+                num.replace(',', ""),
+                num.replace(',', ".")
+            ),
+            DedoError::NotAWholeNumber(num) => write!(f, "Expected a whole number, got {}", num),
+            DedoError::OutsideDomain { function, argument } => {
+                write!(f, "`{}` is not defined for {}", function, argument)
+            }
+            DedoError::RecursiveFunction(name) => write!(f, "`{}` calls itself", name),
+            DedoError::WrongArgumentCount {
+                function,
+                min,
+                max,
+                got,
+            } => {
+                let expected = match max {
+                    Some(max) if max == min => min.to_string(),
+                    Some(max) => format!("{} to {}", min, max),
+                    None => format!("at least {}", min),
+                };
+                write!(f, "`{}` takes {} arguments, got {}", function, expected, got)
+            }
+            DedoError::InvalidRates(reason) => write!(f, "Invalid rates: {}", reason),
+            DedoError::MissingRate(code) => write!(f, "There is no exchange rate for `{}`", code),
+            DedoError::EmptyPrev => write!(f, "There is no previous value"),
+            DedoError::EmptySum => write!(f, "There is nothing to sum"),
+            DedoError::EmptyProd => write!(f, "There is nothing to multiply"),
+            DedoError::NotADuration(units) => {
+                write!(f, "Expected a duration, got {}", describe_units(units))
+            }
+            DedoError::DateArithmetic(operation) => {
+                write!(f, "Dates and times cannot be {}", operation)
+            }
+            DedoError::UnknownTimeZone(zone) => write!(f, "Unknown time zone `{}`", zone),
+        }
+    }
+}
+
+fn describe_units(units: &str) -> String {
+    if units.is_empty() {
+        "a unitless number".into()
+    } else {
+        format!("`{}`", units)
+    }
+}
+
+impl std::error::Error for DedoError {}
+
+impl From<ParseError<LineCol>> for DedoError {
+    fn from(error: ParseError<LineCol>) -> Self {
+        let mut expected: Vec<String> = error.expected.tokens().map(String::from).collect();
+        expected.sort();
+
+        DedoError::Parse {
+            column: error.location.column,
+            expected,
+        }
+    }
+}
+
+/// Errors are sent to the front-end as `{ kind, message }` so it can
+/// show the message next to the offending line
+impl Serialize for DedoError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DedoError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
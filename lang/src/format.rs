@@ -0,0 +1,353 @@
+use std::fmt;
+use serde::{Deserialize, Serialize};
+use crate::bigint::BigInt;
+use crate::currencies::{self, Currency};
+use crate::dates;
+use crate::defaults::ENVIRONMENT;
+use crate::number::{Number, Rational};
+use crate::types::{Layout, Unit, Value};
+
+/// Where a currency goes relative to the amount
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolPlacement {
+    /// Symbols like `$` or `£` go in front, codes like `RON` and lettered
+    /// symbols like `kr` after
+    Auto,
+    Prefix,
+    Suffix,
+}
+
+/// The most decimals results show unless told otherwise
+pub const MAX_DECIMALS: usize = 10;
+
+/// How to render values as text for people
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    /// Exactly this many decimals, zeros included
+    pub decimals: Option<usize>,
+    /// Rounds to this many significant figures instead, zeros included
+    pub significant_figures: Option<usize>,
+    /// Otherwise amounts of money get their currency's minor units and
+    /// everything else up to this many decimals, trailing zeros dropped
+    pub max_decimals: usize,
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+    pub symbol_placement: SymbolPlacement,
+    /// Numbers at least `10^scientific_exponent`, or at most its inverse,
+    /// print like `1.5 × 10⁹`; amounts of money never do
+    pub scientific_exponent: i32,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            decimals: None,
+            significant_figures: None,
+            max_decimals: MAX_DECIMALS,
+            thousands_separator: Some(','),
+            decimal_separator: '.',
+            symbol_placement: SymbolPlacement::Auto,
+            scientific_exponent: 9,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// The options for documents writing decimals with `decimal_separator`,
+    /// grouping thousands with the other one of `.` and `,`
+    pub fn with_decimal_separator(decimal_separator: char) -> FormatOptions {
+        let thousands_separator = if decimal_separator == ',' { '.' } else { ',' };
+        FormatOptions {
+            thousands_separator: Some(thousands_separator),
+            decimal_separator,
+            ..FormatOptions::default()
+        }
+    }
+}
+
+/// Renders a value like `£2,280.00`, `43.2 km/h` or `1.5 × 10⁹ m²`
+///
+/// Example:
+/// ```
+/// # use dedo_rust::format::*;
+/// # use dedo_rust::types::*;
+/// let value = Value::simple(12.5, "RON");
+/// assert_eq!(format_value(&value, &FormatOptions::default()), "12.50 RON");
+/// ```
+pub fn format_value(value: &Value, options: &FormatOptions) -> String {
+    if let Some(signature) = value.signature() {
+        return signature.into();
+    }
+
+    if let Some(calendar) = value.calendar() {
+        let moment = dates::format(calendar, value.number().floor().to_f64() as i64);
+        return match value.zone() {
+            Some(zone) => format!("{} {}", moment, zone),
+            None => moment,
+        };
+    }
+
+    if let Some(mixed) = value.mixed() {
+        let sign = if mixed.negative { "-" } else { "" };
+        let parts = match mixed.layout {
+            Layout::Words => {
+                let parts: Vec<String> =
+                    mixed.parts.iter().map(|part| format_value(part, options)).collect();
+                parts.join(" ")
+            }
+            Layout::Clock => format_clock(&mixed.parts, options),
+        };
+        return format!("{}{}", sign, parts);
+    }
+
+    let currency = currency(value);
+    let num = format_number(&value.number(), currency.map(|(_, currency)| currency), options);
+
+    if let Some((symbol, _)) = currency {
+        let lettered = symbol.chars().all(char::is_alphabetic);
+        let prefix = match options.symbol_placement {
+            SymbolPlacement::Auto => !lettered,
+            SymbolPlacement::Prefix => true,
+            SymbolPlacement::Suffix => false,
+        };
+
+        return match (prefix, num.strip_prefix('-')) {
+            (true, Some(num)) if !lettered => format!("-{}{}", symbol, num),
+            (true, _) if !lettered => format!("{}{}", symbol, num),
+            (true, _) => format!("{} {}", symbol, num),
+            (false, _) => format!("{} {}", num, symbol),
+        };
+    }
+
+    match format_units(value) {
+        units if units.is_empty() => num,
+        units if units == "%" => format!("{}%", num),
+        units => format!("{} {}", num, units),
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", format_value(self, &FormatOptions::default()))
+    }
+}
+
+/// The currency a value is an amount of, with the name it was written with.
+/// Currencies are recognised the way the default environment reads them,
+/// so `usd` is one but `pen` isn't.
+fn currency(value: &Value) -> Option<(&str, &'static Currency)> {
+    let mut units = value.units().0.iter().filter(|(_, pow)| **pow != 0);
+    let (unit, pow) = units.next()?;
+    if *pow != 1 || units.next().is_some() {
+        return None;
+    }
+
+    let code = ENVIRONMENT.units.currency_code(unit)?;
+    Some((&unit.0, currencies::find(&code)?))
+}
+
+/// Renders parts like `01:30:00`, only the last one having decimals
+fn format_clock(parts: &[Value], options: &FormatOptions) -> String {
+    let fields: Vec<String> = parts
+        .iter()
+        .map(|part| {
+            let field = format_number(&part.number(), None, options);
+            match field.find(options.decimal_separator).unwrap_or(field.len()) {
+                1 => format!("0{}", field),
+                _ => field,
+            }
+        })
+        .collect();
+
+    fields.join(":")
+}
+
+/// Units like `km/h` or `m²/(kg·s)`, numerators before denominators and
+/// each sorted by name
+fn format_units(value: &Value) -> String {
+    let mut units: Vec<(&str, i32)> = value
+        .units()
+        .0
+        .iter()
+        .filter(|(_, pow)| **pow != 0)
+        .map(|(Unit(name), pow)| (name.as_str(), *pow))
+        .collect();
+    units.sort();
+
+    let render = |units: Vec<(&str, i32)>| -> String {
+        units
+            .iter()
+            .map(|(name, pow)| match pow {
+                1 => name.to_string(),
+                pow => format!("{}{}", name, superscript(*pow)),
+            })
+            .collect::<Vec<String>>()
+            .join("·")
+    };
+
+    let numerator: Vec<(&str, i32)> = units.iter().filter(|(_, pow)| *pow > 0).copied().collect();
+    let denominator: Vec<(&str, i32)> =
+        units.iter().filter(|(_, pow)| *pow < 0).map(|(name, pow)| (*name, -pow)).collect();
+
+    match (numerator.len(), denominator.len()) {
+        (_, 0) => render(numerator),
+        // nothing to divide, so `s⁻¹` rather than `1/s`
+        (0, _) => render(units),
+        (_, 1) => format!("{}/{}", render(numerator), render(denominator)),
+        _ => format!("{}/({})", render(numerator), render(denominator)),
+    }
+}
+
+fn format_number(num: &Number, currency: Option<&Currency>, options: &FormatOptions) -> String {
+    let num = match num {
+        Number::Float(num) if num.is_nan() => return "NaN".into(),
+        Number::Float(num) if num.is_infinite() && *num > 0.0 => return "∞".into(),
+        Number::Float(num) if num.is_infinite() => return "-∞".into(),
+        num => num.to_rational().or_else(|| Rational::from_f64(num.to_f64())).unwrap(),
+    };
+    if num.is_zero() {
+        return render_decimal(&num, decimals(0, currency, options), options);
+    }
+
+    let mut exponent = exponent(&num);
+    if options.decimals.is_none() && options.significant_figures.is_some() {
+        // so 99.96 to three figures is `100`, not `100.0`
+        let places = decimals(exponent, currency, options).places;
+        let rounded = Rational::from(expand(&num, places, true));
+        exponent = exponent.max(self::exponent(&rounded) - places);
+    }
+    let scientific = currency.is_none()
+        && options.scientific_exponent > 0
+        && exponent.abs() >= options.scientific_exponent;
+    if !scientific {
+        return render_decimal(&num, decimals(exponent, currency, options), options);
+    }
+
+    // rounding can carry into another digit, like 9.99 to 10.0
+    let decimals = decimals(0, None, options);
+    let mut digits = expand(&num, decimals.places - exponent, true);
+    if digits >= power_of_ten(decimals.places as u32 + 1) {
+        exponent += 1;
+        digits = expand(&num, decimals.places - exponent, true);
+    }
+    let digits = if num.numerator().is_negative() { -digits } else { digits };
+    let mantissa = Rational::new(digits, power_of_ten(decimals.places as u32));
+
+    format!(
+        "{} × 10{}",
+        render_decimal(&mantissa, decimals, options),
+        superscript(exponent)
+    )
+}
+
+/// How many decimals to round to, and whether to keep trailing zeros
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Decimals {
+    places: i32,
+    trim: bool,
+}
+
+/// The decimals for a number whose leading digit is at `exponent`
+fn decimals(exponent: i32, currency: Option<&Currency>, options: &FormatOptions) -> Decimals {
+    match (options.decimals, options.significant_figures, currency) {
+        (Some(decimals), _, _) => Decimals {
+            places: decimals as i32,
+            trim: false,
+        },
+        (None, Some(figures), _) => Decimals {
+            places: figures.max(1) as i32 - 1 - exponent,
+            trim: false,
+        },
+        (None, None, Some(currency)) => Decimals {
+            places: currency.minor_units,
+            trim: false,
+        },
+        (None, None, None) => Decimals {
+            places: options.max_decimals as i32,
+            trim: true,
+        },
+    }
+}
+
+/// Writes out `num` rounded to `decimals`, with thousands grouped
+fn render_decimal(num: &Rational, decimals: Decimals, options: &FormatOptions) -> String {
+    let places = decimals.places.max(0) as usize;
+    let digits = match expand(num, decimals.places, true) {
+        // rounded to tens, hundreds and so on
+        digits if decimals.places < 0 => &digits * &power_of_ten(decimals.places.unsigned_abs()),
+        digits => digits,
+    };
+
+    let sign = if num.numerator().is_negative() && !digits.is_zero() { "-" } else { "" };
+    let digits = format!("{:0>width$}", digits.to_string(), width = places + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - places);
+    let fraction = if decimals.trim { fraction.trim_end_matches('0') } else { fraction };
+
+    let whole = match options.thousands_separator {
+        Some(separator) => group_thousands(whole, separator),
+        None => whole.to_string(),
+    };
+    match fraction {
+        "" => format!("{}{}", sign, whole),
+        fraction => format!("{}{}{}{}", sign, whole, options.decimal_separator, fraction),
+    }
+}
+
+fn group_thousands(digits: &str, separator: char) -> String {
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// The power of ten of the leading digit, e.g. 3 for 2,280 and -2 for 0.05
+fn exponent(num: &Rational) -> i32 {
+    // the bit lengths put it within one either way, so expanding to the
+    // place after the estimated leading digit leaves one to three digits
+    let bits = num.numerator().bits() as f64 - num.denominator().bits() as f64;
+    let estimate = (bits * std::f64::consts::LOG10_2).floor() as i32;
+    let digits = expand(num, 1 - estimate, false).to_string().len() as i32;
+    digits - 1 - (1 - estimate)
+}
+
+/// The digits of `|num|` down to `places` decimals (or up to tens, hundreds
+/// and so on when negative), as one whole number: rounded half away from
+/// zero, or else cut off
+fn expand(num: &Rational, places: i32, round: bool) -> BigInt {
+    let power = power_of_ten(places.unsigned_abs());
+    let (numerator, denominator) = match places {
+        places if places >= 0 => (&num.numerator().abs() * &power, num.denominator().clone()),
+        _ => (num.numerator().abs(), num.denominator() * &power),
+    };
+
+    let (quotient, remainder) = numerator.div_rem(&denominator);
+    if round && &remainder + &remainder >= denominator {
+        &quotient + &BigInt::one()
+    } else {
+        quotient
+    }
+}
+
+fn power_of_ten(exponent: u32) -> BigInt {
+    BigInt::from(10).pow(exponent)
+}
+
+/// Writes an integer like `-12` as `⁻¹²`
+fn superscript(num: i32) -> String {
+    num.to_string()
+        .chars()
+        .map(|c| match c {
+            '-' => '⁻',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            c => char::from_u32(0x2070 + c.to_digit(10).unwrap()).unwrap(),
+        })
+        .collect()
+}
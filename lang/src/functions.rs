@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+use crate::ast::Term;
+use crate::bigint::BigInt;
+use crate::errors::DedoError;
+use crate::number::Number;
+use crate::types::*;
+use crate::units::{Dimension, Dimensions};
+
+/// A function every document can call, e.g. `sqrt(16 m^2)`
+#[derive(Clone, Copy, Debug)]
+pub struct Builtin {
+    pub min_args: usize,
+    /// `None` for variadic functions like `max`
+    pub max_args: Option<usize>,
+    pub apply: fn(&Environment, Vec<Value>) -> Result<Value, DedoError>,
+}
+
+impl Builtin {
+    fn new(
+        min_args: usize,
+        max_args: Option<usize>,
+        apply: fn(&Environment, Vec<Value>) -> Result<Value, DedoError>,
+    ) -> Builtin {
+        Builtin {
+            min_args,
+            max_args,
+            apply,
+        }
+    }
+
+    pub fn accepts(&self, args: usize) -> bool {
+        args >= self.min_args && self.max_args.is_none_or(|max| args <= max)
+    }
+}
+
+impl PartialEq for Builtin {
+    fn eq(&self, other: &Self) -> bool {
+        self.min_args == other.min_args
+            && self.max_args == other.max_args
+            && std::ptr::fn_addr_eq(self.apply, other.apply)
+    }
+}
+
+/// A function defined in the document, e.g. `tax(x) = x * 20%`
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserFunction {
+    pub params: Vec<String>,
+    pub body: Term,
+    /// The document's variables at the point of definition
+    pub captured: HashMap<String, Value>,
+}
+
+impl UserFunction {
+    /// The variables the body is evaluated with: the captured ones, with
+    /// the parameters bound on top
+    pub fn scope(&self, args: Vec<Value>) -> HashMap<String, Value> {
+        let mut scope = self.captured.clone();
+        scope.extend(self.params.iter().cloned().zip(args));
+        scope
+    }
+}
+
+pub fn builtins() -> HashMap<String, Builtin> {
+    let mut functions = HashMap::new();
+    let mut define = |name: &str, builtin: Builtin| {
+        functions.insert(name.to_string(), builtin);
+    };
+
+    define("sqrt", Builtin::new(1, Some(1), |_, args| root(&args[0], 2)));
+    define("cbrt", Builtin::new(1, Some(1), |_, args| root(&args[0], 3)));
+    define("abs", Builtin::new(1, Some(1), |_, args| Ok(keep_units(&args[0], Number::abs))));
+    define("floor", Builtin::new(1, Some(1), |_, args| Ok(keep_units(&args[0], Number::floor))));
+    define("ceil", Builtin::new(1, Some(1), |_, args| Ok(keep_units(&args[0], Number::ceil))));
+    define("round", Builtin::new(1, Some(2), round));
+    define("min", Builtin::new(1, None, |env, args| extremum(env, args, |diff| diff < 0.0)));
+    define("max", Builtin::new(1, None, |env, args| extremum(env, args, |diff| diff > 0.0)));
+    define("ln", Builtin::new(1, Some(1), |_, args| ln(&args[0])));
+    define("exp", Builtin::new(1, Some(1), |_, args| of_unitless(&args[0], f64::exp)));
+    define("log", Builtin::new(1, Some(2), log));
+    define("factorial", Builtin::new(1, Some(1), |_, args| factorial(&args[0])));
+    define("sin", Builtin::new(1, Some(1), |env, args| of_angle(env, &args[0], f64::sin)));
+    define("cos", Builtin::new(1, Some(1), |env, args| of_angle(env, &args[0], f64::cos)));
+    define("tan", Builtin::new(1, Some(1), |env, args| of_angle(env, &args[0], f64::tan)));
+
+    functions
+}
+
+fn keep_units(value: &Value, f: fn(Number) -> Number) -> Value {
+    Value::new(f(value.number()), value.units().clone())
+}
+
+fn of_unitless(value: &Value, f: fn(f64) -> f64) -> Result<Value, DedoError> {
+    Ok(Value::unitless(f(unitless(value)?)))
+}
+
+/// Fails with `OutsideDomain` unless `in_domain`, e.g. for `sqrt(-1)`
+fn check_domain(function: &str, value: &Value, in_domain: bool) -> Result<(), DedoError> {
+    if in_domain {
+        return Ok(());
+    }
+
+    Err(DedoError::OutsideDomain {
+        function: function.into(),
+        argument: value.number().to_string(),
+    })
+}
+
+fn of_angle(env: &Environment, value: &Value, f: fn(f64) -> f64) -> Result<Value, DedoError> {
+    Ok(Value::unitless(f(radians(env, value)?)))
+}
+
+fn unitless(value: &Value) -> Result<f64, DedoError> {
+    if value.is_unitless() {
+        Ok(value.num())
+    } else {
+        Err(DedoError::IncompatibleUnits(value.units().to_string(), "".into()))
+    }
+}
+
+/// The `n`th root, halving (or thirding) the unit powers, so
+/// `sqrt(16 m^2)` is `4 m`
+fn root(value: &Value, n: i32) -> Result<Value, DedoError> {
+    let units = value.units();
+    if units.0.values().any(|pow| pow % n != 0) {
+        return Err(DedoError::FractionalUnitPower(units.to_string()));
+    }
+
+    if n == 2 {
+        check_domain("sqrt", value, value.num() >= 0.0)?;
+    }
+
+    let rooted = UnitSet(units.0.iter().map(|(unit, pow)| (unit.clone(), pow / n)).collect());
+    let num = match n {
+        2 => value.num().sqrt(),
+        3 => value.num().cbrt(),
+        _ => value.num().powf(1.0 / n as f64),
+    };
+
+    Ok(Value::new(num, rooted))
+}
+
+/// Rounds to `digits` decimals the way people expect, so 12.345 becomes
+/// 12.35 even though it is stored as 12.3449999...
+pub(crate) fn round_to(num: f64, digits: i32) -> f64 {
+    if !num.is_finite() {
+        return num;
+    }
+
+    // shifting the decimal point through the shortest representation of
+    // `num` avoids the binary error of multiplying by a power of 10; when
+    // that overflows, `num` has no decimals left to round at `digits`
+    let shifted = match format!("{}e{}", num, digits).parse::<f64>() {
+        Ok(shifted) if shifted.is_finite() => shifted,
+        _ => return num,
+    };
+    format!("{}e{}", shifted.round(), -digits).parse().unwrap_or(num)
+}
+
+fn round(_: &Environment, args: Vec<Value>) -> Result<Value, DedoError> {
+    let digits = match args.get(1) {
+        Some(digits) => unitless(digits)?.round() as i32,
+        None => 0,
+    };
+
+    Ok(Value::new(args[0].number().round(digits), args[0].units().clone()))
+}
+
+/// `min` and `max`, comparing values in whichever units they're in
+fn extremum(
+    env: &Environment,
+    args: Vec<Value>,
+    is_better: fn(f64) -> bool,
+) -> Result<Value, DedoError> {
+    let mut args = args.into_iter();
+    let mut best = args.next().unwrap();
+
+    for arg in args {
+        if is_better(env.sub(arg.clone(), best.clone())?.num()) {
+            best = arg;
+        }
+    }
+
+    Ok(best)
+}
+
+fn ln(value: &Value) -> Result<Value, DedoError> {
+    let num = unitless(value)?;
+    check_domain("ln", value, num > 0.0)?;
+    Ok(Value::unitless(num.ln()))
+}
+
+/// `log(x)` is base 10, `log(x, base)` is any base
+fn log(_: &Environment, args: Vec<Value>) -> Result<Value, DedoError> {
+    let num = unitless(&args[0])?;
+    check_domain("log", &args[0], num > 0.0)?;
+    let log = match args.get(1) {
+        Some(base) => {
+            let base_num = unitless(base)?;
+            check_domain("log", base, base_num > 0.0 && base_num != 1.0)?;
+            num.log(base_num)
+        }
+        // `log10` is exact for powers of 10, `log(10.0)` is not
+        None => num.log10(),
+    };
+
+    Ok(Value::unitless(log))
+}
+
+/// `n!`, exact for as long as it fits in an exact number
+fn factorial(value: &Value) -> Result<Value, DedoError> {
+    let num = match value.number() {
+        Number::Integer(num) if !num.is_negative() && value.is_unitless() => num,
+        _ if !value.is_unitless() => {
+            return Err(DedoError::IncompatibleUnits(value.units().to_string(), "".into()))
+        }
+        num => return Err(DedoError::NotAWholeNumber(num.to_string())),
+    };
+
+    let mut product = Number::integer(1);
+    let mut factor = BigInt::one();
+    while factor <= num {
+        product = product * Number::Integer(factor.clone());
+        factor = &factor + &BigInt::one();
+        if !product.is_exact() && product.to_f64().is_infinite() {
+            break;
+        }
+    }
+
+    Ok(Value::unitless(product))
+}
+
+/// Plain numbers are radians, anything else must be an angle like `30 deg`
+fn radians(env: &Environment, value: &Value) -> Result<f64, DedoError> {
+    if value.is_unitless() {
+        return Ok(value.num());
+    }
+
+    let def = env.units.resolve(value.units());
+    if def.dimensions != Dimensions::from(Dimension::Angle) {
+        return Err(DedoError::IncompatibleUnits(value.units().to_string(), "rad".into()));
+    }
+
+    Ok(value.num() * def.factor)
+}
@@ -9,16 +9,30 @@ extern crate wasm_bindgen;
 
 #[macro_use]
 pub mod types;
+pub mod bigint;
+pub mod currencies;
+pub mod dates;
 pub mod defaults;
+pub mod errors;
+pub mod format;
+pub mod functions;
+pub mod number;
 pub mod parser;
 pub mod ast;
+pub mod rates;
 pub mod runtime;
+pub mod units;
+pub mod zones;
 
 // test
+mod test_format;
+mod test_number;
 mod test_parser;
+mod test_rates;
 mod test_types;
 
 use cfg_if::cfg_if;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
 cfg_if! {
@@ -31,8 +45,135 @@ cfg_if! {
     }
 }
 
+/// A value as the front-end gets it, with the text to show for it
+#[derive(Serialize)]
+struct Formatted<'a> {
+    #[serde(flatten)]
+    value: &'a types::Value,
+    formatted: String,
+}
+
+fn to_js(
+    results: &[Result<types::Value, errors::DedoError>],
+    options: &format::FormatOptions,
+) -> JsValue {
+    let results: Vec<Result<Formatted, &errors::DedoError>> = results
+        .iter()
+        .map(|result| {
+            result.as_ref().map(|value| Formatted {
+                value,
+                formatted: format::format_value(value, options),
+            })
+        })
+        .collect();
+    JsValue::from_serde(&results).unwrap()
+}
+
 #[wasm_bindgen]
 pub fn parse_input(s: &str) -> JsValue {
     let result = parser::parse(s);
-    JsValue::from_serde(&result).unwrap()
-}
\ No newline at end of file
+    to_js(&result, &format::FormatOptions::default())
+}
+
+/// How the front-end wants a document read and shown; every field is
+/// optional
+#[derive(Deserialize)]
+#[serde(default)]
+struct ParseOptions {
+    /// `.` or `,`, worked out from each number when not given
+    decimal_separator: Option<char>,
+    unit_selection: types::UnitSelection,
+    arithmetic: number::Arithmetic,
+    /// Currencies are converted with the rates `rates.rate(code)` returns,
+    /// quoted against `rates.base`, instead of the built-in ones
+    #[cfg(target_arch = "wasm32")]
+    rates: Option<HostRatesOptions>,
+    /// Defaults to grouping thousands with whichever of `.` and `,` isn't
+    /// the decimal separator
+    format: Option<format::FormatOptions>,
+}
+
+/// The parts of `ParseOptions::rates` besides the `rate` function
+#[cfg(target_arch = "wasm32")]
+#[derive(Deserialize)]
+struct HostRatesOptions {
+    base: String,
+    timestamp: Option<String>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            decimal_separator: None,
+            unit_selection: types::UnitSelection::AsWritten,
+            arithmetic: number::Arithmetic::Exact,
+            #[cfg(target_arch = "wasm32")]
+            rates: None,
+            format: None,
+        }
+    }
+}
+
+impl ParseOptions {
+    fn environment(&self) -> types::Environment {
+        let mut env = defaults::ENVIRONMENT.clone();
+        env.set_number_format(match self.decimal_separator {
+            Some('.') => number::NumberFormat::DecimalPoint,
+            Some(',') => number::NumberFormat::DecimalComma,
+            _ => number::NumberFormat::Auto,
+        });
+        env.set_unit_selection(self.unit_selection);
+        env.set_arithmetic(self.arithmetic);
+        env
+    }
+
+    fn format(&self) -> format::FormatOptions {
+        match (&self.format, self.decimal_separator) {
+            (Some(options), _) => options.clone(),
+            (None, Some(separator)) => format::FormatOptions::with_decimal_separator(separator),
+            (None, None) => format::FormatOptions::default(),
+        }
+    }
+}
+
+/// Like `parse_input`, read and formatted as `options` says, an object
+/// with any of the fields of `ParseOptions`, e.g.
+/// `{ decimal_separator: ",", rates: { base: "EUR", rate: code => 1.1 } }`
+#[wasm_bindgen]
+pub fn parse_input_with_options(s: &str, options: JsValue) -> Result<JsValue, JsValue> {
+    let parsed: ParseOptions = if options.is_undefined() {
+        ParseOptions::default()
+    } else {
+        options.into_serde().map_err(|err| JsValue::from_str(&err.to_string()))?
+    };
+
+    let env = parsed.environment();
+    #[cfg(target_arch = "wasm32")]
+    let env = with_host_rates(env, &options, &parsed)?;
+
+    let result = parser::parse_in(env, s);
+    Ok(to_js(&result, &parsed.format()))
+}
+
+/// Hands currency conversion to the `rates.rate` function of the options,
+/// if they have one
+#[cfg(target_arch = "wasm32")]
+fn with_host_rates(
+    mut env: types::Environment,
+    options: &JsValue,
+    parsed: &ParseOptions,
+) -> Result<types::Environment, JsValue> {
+    use wasm_bindgen::JsCast;
+
+    let rates = match &parsed.rates {
+        Some(rates) => rates,
+        None => return Ok(env),
+    };
+    let rate = js_sys::Reflect::get(options, &"rates".into())
+        .and_then(|rates| js_sys::Reflect::get(&rates, &"rate".into()))?
+        .dyn_into::<js_sys::Function>()
+        .map_err(|_| JsValue::from_str("`rates.rate` must be a function"))?;
+    let provider = rates::HostRates::new(rates.base.clone(), rates.timestamp.clone(), rate);
+    env.set_rate_provider(std::sync::Arc::new(provider));
+    Ok(env)
+}
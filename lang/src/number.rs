@@ -0,0 +1,636 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::bigint::BigInt;
+
+/// Exact numbers bigger than this many bits become floats, so something
+/// like `10 ^ 10 ^ 10` can't exhaust memory
+const MAX_BITS: u64 = 1 << 17;
+
+/// How many decimals a fraction like `2/3` prints with
+const DISPLAY_DIGITS: i32 = 28;
+
+/// An exact fraction, kept in lowest terms with a positive denominator
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rational {
+    numerator: BigInt,
+    denominator: BigInt,
+}
+
+impl Rational {
+    /// Panics if `denominator` is zero
+    pub fn new(numerator: BigInt, denominator: BigInt) -> Rational {
+        assert!(!denominator.is_zero(), "zero denominator");
+
+        let (mut numerator, mut denominator) = (numerator, denominator);
+        // whole numbers, the usual case, need no reducing at all
+        let gcd = if denominator == BigInt::one() {
+            BigInt::one()
+        } else {
+            numerator.gcd(&denominator)
+        };
+        if gcd != BigInt::one() {
+            numerator = numerator.div_rem(&gcd).0;
+            denominator = denominator.div_rem(&gcd).0;
+        }
+        if denominator.is_negative() {
+            numerator = -numerator;
+            denominator = -denominator;
+        }
+
+        Rational {
+            numerator,
+            denominator,
+        }
+    }
+
+    pub fn numerator(&self) -> &BigInt {
+        &self.numerator
+    }
+
+    pub fn denominator(&self) -> &BigInt {
+        &self.denominator
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.denominator == BigInt::one()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.numerator.is_zero()
+    }
+
+    /// The fraction a `f64` prints as, so `0.1` is exactly one tenth
+    pub fn from_f64(num: f64) -> Option<Rational> {
+        if !num.is_finite() {
+            return None;
+        }
+
+        num.to_string().parse().ok()
+    }
+
+    /// The closest `f64`
+    pub fn to_f64(&self) -> f64 {
+        // both are exact as floats, and float division rounds correctly
+        if self.numerator.bits() <= 53 && self.denominator.bits() <= 53 {
+            return self.numerator.to_f64() / self.denominator.to_f64();
+        }
+
+        // otherwise take a quotient with more bits than a float holds
+        let shift = self.denominator.bits() as i64 - self.numerator.bits() as i64 + 64;
+        let power = BigInt::from(2).pow(shift.unsigned_abs() as u32);
+        let quotient = if shift >= 0 {
+            (&self.numerator * &power).div_rem(&self.denominator).0
+        } else {
+            self.numerator.div_rem(&(&self.denominator * &power)).0
+        };
+
+        // scale back in two steps, so the intermediate doesn't underflow
+        let half = (shift / 2) as i32;
+        quotient.to_f64() * 2f64.powi(-half) * 2f64.powi(half - shift as i32)
+    }
+
+    /// How big the fraction is to store
+    fn bits(&self) -> u64 {
+        self.numerator.bits().max(self.denominator.bits())
+    }
+
+    pub fn abs(&self) -> Rational {
+        Rational {
+            numerator: self.numerator.abs(),
+            denominator: self.denominator.clone(),
+        }
+    }
+
+    pub fn recip(&self) -> Option<Rational> {
+        if self.is_zero() {
+            return None;
+        }
+
+        Some(Rational::new(self.denominator.clone(), self.numerator.clone()))
+    }
+
+    /// The exact `n`th root, if both parts are perfect powers
+    pub fn root(&self, n: u32) -> Option<Rational> {
+        Some(Rational {
+            numerator: self.numerator.root(n)?,
+            denominator: self.denominator.root(n)?,
+        })
+    }
+
+    pub fn pow(&self, exponent: i32) -> Option<Rational> {
+        let magnitude = exponent.unsigned_abs();
+        let power = Rational {
+            numerator: self.numerator.pow(magnitude),
+            denominator: self.denominator.pow(magnitude),
+        };
+
+        if exponent < 0 {
+            power.recip()
+        } else {
+            Some(power)
+        }
+    }
+
+    /// Rounds towards negative infinity
+    pub fn floor(&self) -> BigInt {
+        let (quotient, remainder) = self.numerator.div_rem(&self.denominator);
+        if remainder.is_negative() {
+            &quotient - &BigInt::one()
+        } else {
+            quotient
+        }
+    }
+
+    /// Rounds towards positive infinity
+    pub fn ceil(&self) -> BigInt {
+        -(-self.clone()).floor()
+    }
+
+    /// Rounds half away from zero to `digits` decimals; negative `digits`
+    /// round to tens, hundreds and so on. Like literals, at most
+    /// `MAX_EXPONENT` digits are kept either way.
+    pub fn round(&self, digits: i32) -> Rational {
+        let magnitude = digits.unsigned_abs().min(MAX_EXPONENT);
+        let shift = Rational::from(BigInt::from(10).pow(magnitude));
+        let shift = if digits < 0 { shift.recip().unwrap() } else { shift };
+
+        let shifted = self * &shift;
+        let (mut whole, remainder) = shifted.numerator.div_rem(&shifted.denominator);
+        if &remainder.abs() + &remainder.abs() >= shifted.denominator {
+            let away = if shifted.numerator.is_negative() { -BigInt::one() } else { BigInt::one() };
+            whole = &whole + &away;
+        }
+
+        &Rational::from(whole) / &shift
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(num: BigInt) -> Self {
+        Rational {
+            numerator: num,
+            denominator: BigInt::one(),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // denominators are positive, so cross multiplying keeps the order
+        (&self.numerator * &other.denominator).cmp(&(&other.numerator * &self.denominator))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Rational {
+        Rational {
+            numerator: -self.numerator,
+            ..self
+        }
+    }
+}
+
+impl<'a> Add<&'a Rational> for &'a Rational {
+    type Output = Rational;
+
+    fn add(self, other: &Rational) -> Rational {
+        Rational::new(
+            &(&self.numerator * &other.denominator) + &(&other.numerator * &self.denominator),
+            &self.denominator * &other.denominator,
+        )
+    }
+}
+
+impl<'a> Sub<&'a Rational> for &'a Rational {
+    type Output = Rational;
+
+    fn sub(self, other: &Rational) -> Rational {
+        self + &-other.clone()
+    }
+}
+
+impl<'a> Mul<&'a Rational> for &'a Rational {
+    type Output = Rational;
+
+    fn mul(self, other: &Rational) -> Rational {
+        Rational::new(
+            &self.numerator * &other.numerator,
+            &self.denominator * &other.denominator,
+        )
+    }
+}
+
+impl<'a> Div<&'a Rational> for &'a Rational {
+    type Output = Rational;
+
+    /// Panics if `other` is zero
+    fn div(self, other: &Rational) -> Rational {
+        assert!(!other.is_zero(), "division by zero");
+        Rational::new(
+            &self.numerator * &other.denominator,
+            &self.denominator * &other.numerator,
+        )
+    }
+}
+
+impl FromStr for Rational {
+    type Err = ();
+
+    /// Plain decimal notation, e.g. `-12.5`, `.5` or `12.`
+    fn from_str(s: &str) -> Result<Rational, ()> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s),
+        };
+        let (whole, fraction) = match digits.split_once('.') {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (digits, ""),
+        };
+        if whole.is_empty() && fraction.is_empty() {
+            return Err(());
+        }
+
+        let numerator: BigInt = format!("{}{}", whole, fraction).parse()?;
+        let numerator = if negative { -numerator } else { numerator };
+        Ok(Rational::new(numerator, BigInt::from(10).pow(fraction.len() as u32)))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.numerator)
+        } else {
+            write!(f, "{}/{}", self.numerator, self.denominator)
+        }
+    }
+}
+
+/// The biggest power of ten a literal like `1e300` can have, so that
+/// `1e999999999` can't exhaust memory
+const MAX_EXPONENT: u32 = 10_000;
+
+/// Which of `.` and `,` separates decimals in the document, the other one
+/// grouping thousands. `_` can group digits in any format, e.g. `1_000`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum NumberFormat {
+    /// Worked out from the literal: `1,250.5` and `1.250,5` are both fine,
+    /// as are `1.5` and `1.250.000`, but `1,250` could be read either way
+    Auto,
+    /// `1,250,000.5`
+    DecimalPoint,
+    /// `1.250.000,5`
+    DecimalComma,
+}
+
+impl NumberFormat {
+    /// The value of a decimal literal like `1,250.5` or `6.02e23`, if it
+    /// is written correctly for this format
+    pub fn parse(self, literal: &str) -> Option<Rational> {
+        let (mantissa, exponent) = match literal.find(['e', 'E']) {
+            Some(index) => (&literal[..index], literal[index + 1..].parse::<i32>().ok()?),
+            None => (literal, 0),
+        };
+        if exponent.unsigned_abs() > MAX_EXPONENT {
+            return None;
+        }
+
+        let mantissa = mantissa.replace('_', "");
+        let (group, decimal) = match self {
+            NumberFormat::DecimalPoint => (',', '.'),
+            NumberFormat::DecimalComma => ('.', ','),
+            // whichever separator comes last separates the decimals, and
+            // the one that repeats groups thousands
+            NumberFormat::Auto => match (mantissa.rfind('.'), mantissa.rfind(',')) {
+                (Some(dot), Some(comma)) if comma > dot => ('.', ','),
+                (Some(_), None) if mantissa.matches('.').count() > 1 => ('.', ','),
+                _ => (',', '.'),
+            },
+        };
+
+        let (whole, fraction) = mantissa.split_once(decimal).unwrap_or((&mantissa, ""));
+        if fraction.contains([group, decimal]) {
+            return None;
+        }
+
+        // thousands come in threes after a first group of one to three
+        let groups: Vec<&str> = whole.split(group).collect();
+        let misgrouped = !(1..=3).contains(&groups[0].len())
+            || groups[1..].iter().any(|group| group.len() != 3);
+        if groups.len() > 1 && misgrouped {
+            return None;
+        }
+
+        let num: Rational = format!("{}.{}", groups.concat(), fraction).parse().ok()?;
+        let shift = Rational::from(BigInt::from(10).pow(exponent.unsigned_abs()));
+        if exponent < 0 {
+            Some(&num / &shift)
+        } else {
+            Some(&num * &shift)
+        }
+    }
+}
+
+/// How an environment does arithmetic
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arithmetic {
+    /// Binary floating point, fast but `0.1 + 0.2` is `0.30000000000000004`
+    Float,
+    /// Exact integers and fractions, falling back to floats only where
+    /// they have to
+    Exact,
+}
+
+/// The number of a `Value`, one of a tower of integers, fractions and
+/// floats. Integers and fractions stay exact through `+`, `-`, `*`, `/`
+/// and whole powers, with fractions that come out whole turning back into
+/// integers; anything involving a float, a transcendental function, or an
+/// exact number too big to store falls back to a float.
+#[derive(Clone, Debug)]
+pub enum Number {
+    Integer(BigInt),
+    Rational(Rational),
+    Float(f64),
+}
+
+impl Number {
+    /// A literal as written in the document, exact unless the environment
+    /// does float arithmetic
+    pub fn literal(num: Rational, arithmetic: Arithmetic) -> Number {
+        match arithmetic {
+            Arithmetic::Exact => Number::from(num),
+            Arithmetic::Float => Number::Float(num.to_f64()),
+        }
+    }
+
+    /// A whole number, e.g. the `100` in percentages. Combined with a float
+    /// it gives the same float as `num as f64` would.
+    pub fn integer(num: i64) -> Number {
+        Number::Integer(BigInt::from(num))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Integer(num) => num.to_f64(),
+            Number::Rational(num) => num.to_f64(),
+            Number::Float(num) => *num,
+        }
+    }
+
+    /// A float known to be exact as it prints, like a conversion factor
+    /// of `0.3048`; infinities and NaN stay floats
+    pub fn exact(num: f64) -> Number {
+        match Rational::from_f64(num) {
+            Some(num) => Number::from(num),
+            None => Number::Float(num),
+        }
+    }
+
+    /// The exact value, unless this is a float
+    pub fn to_rational(&self) -> Option<Rational> {
+        match self {
+            Number::Integer(num) => Some(Rational::from(num.clone())),
+            Number::Rational(num) => Some(num.clone()),
+            Number::Float(_) => None,
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        !matches!(self, Number::Float(_))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Integer(num) => num.is_zero(),
+            Number::Rational(num) => num.is_zero(),
+            Number::Float(num) => *num == 0.0,
+        }
+    }
+
+    fn combine(
+        self,
+        other: Number,
+        exact: fn(&Rational, &Rational) -> Option<Rational>,
+        float: fn(f64, f64) -> f64,
+    ) -> Number {
+        if let (Some(left), Some(right)) = (self.to_rational(), other.to_rational()) {
+            if let Some(result) = exact(&left, &right) {
+                return Number::from(result);
+            }
+        }
+
+        Number::Float(float(self.to_f64(), other.to_f64()))
+    }
+
+    pub fn recip(self) -> Number {
+        Number::integer(1) / self
+    }
+
+    pub fn pow(self, exponent: i32) -> Number {
+        if let Some(num) = self.to_rational() {
+            // checked up front, since working out a huge power is the slow part
+            let bits = num.bits().saturating_mul(exponent.unsigned_abs() as u64);
+            if bits <= MAX_BITS {
+                if let Some(power) = num.pow(exponent) {
+                    return Number::from(power);
+                }
+            }
+        }
+
+        Number::Float(self.to_f64().powf(exponent as f64))
+    }
+
+    /// Raises to any power: exactly for whole exponents and roots of
+    /// perfect powers, and through floats otherwise, so `4 ^ 0.5` is `2`
+    /// and `2 ^ 0.5` is `1.414...`
+    pub fn raise(self, exponent: &Number) -> Number {
+        let exact = match exponent {
+            Number::Float(exponent) if exponent.fract() == 0.0 => {
+                Some(Rational::from(BigInt::from(*exponent as i64)))
+            }
+            Number::Float(_) => None,
+            exponent => exponent.to_rational(),
+        };
+        let small = |num: &BigInt| num.to_i64().and_then(|num| i32::try_from(num).ok());
+
+        if let Some(exponent) = exact {
+            let power = small(exponent.numerator());
+            let root = small(exponent.denominator());
+            if let (Some(power), Some(root)) = (power, root) {
+                if root == 1 {
+                    return self.pow(power);
+                }
+
+                let rooted = self.to_rational().and_then(|num| num.root(root as u32));
+                if let Some(rooted) = rooted {
+                    return Number::from(rooted).pow(power);
+                }
+            }
+        }
+
+        Number::Float(self.to_f64().powf(exponent.to_f64()))
+    }
+
+    /// Rounds to `digits` decimals; see `functions::round_to` for floats
+    pub fn round(self, digits: i32) -> Number {
+        match self.to_rational() {
+            Some(num) => Number::from(num.round(digits)),
+            None => Number::Float(crate::functions::round_to(self.to_f64(), digits)),
+        }
+    }
+
+    pub fn floor(self) -> Number {
+        match self.to_rational() {
+            Some(num) => Number::Integer(num.floor()),
+            None => Number::Float(self.to_f64().floor()),
+        }
+    }
+
+    pub fn ceil(self) -> Number {
+        match self.to_rational() {
+            Some(num) => Number::Integer(num.ceil()),
+            None => Number::Float(self.to_f64().ceil()),
+        }
+    }
+
+    pub fn abs(self) -> Number {
+        match self {
+            Number::Integer(num) => Number::Integer(num.abs()),
+            Number::Rational(num) if num.numerator().is_negative() => Number::Rational(-num),
+            Number::Float(num) => Number::Float(num.abs()),
+            num => num,
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(num: f64) -> Self {
+        Number::Float(num)
+    }
+}
+
+/// The simplest exact number for `num`: an integer if it is whole, and a
+/// float if it got too big
+impl From<Rational> for Number {
+    fn from(num: Rational) -> Self {
+        if num.bits() > MAX_BITS {
+            Number::Float(num.to_f64())
+        } else if num.is_integer() {
+            Number::Integer(num.numerator)
+        } else {
+            Number::Rational(num)
+        }
+    }
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.to_rational(), other.to_rational()) {
+            (Some(left), Some(right)) => left == right,
+            _ => self.to_f64() == other.to_f64(),
+        }
+    }
+}
+
+impl Add for Number {
+    type Output = Number;
+
+    fn add(self, other: Number) -> Number {
+        self.combine(other, |left, right| Some(left + right), |left, right| left + right)
+    }
+}
+
+impl Sub for Number {
+    type Output = Number;
+
+    fn sub(self, other: Number) -> Number {
+        self.combine(other, |left, right| Some(left - right), |left, right| left - right)
+    }
+}
+
+impl Mul for Number {
+    type Output = Number;
+
+    fn mul(self, other: Number) -> Number {
+        self.combine(other, |left, right| Some(left * right), |left, right| left * right)
+    }
+}
+
+impl Div for Number {
+    type Output = Number;
+
+    /// Dividing by an exact zero gives an infinite float, like floats do
+    fn div(self, other: Number) -> Number {
+        self.combine(
+            other,
+            |left, right| Some(left * &right.recip()?),
+            |left, right| left / right,
+        )
+    }
+}
+
+impl Neg for Number {
+    type Output = Number;
+
+    fn neg(self) -> Number {
+        match self {
+            Number::Integer(num) => Number::Integer(-num),
+            Number::Rational(num) => Number::Rational(-num),
+            Number::Float(num) => Number::Float(-num),
+        }
+    }
+}
+
+/// Integers print in full, fractions as decimals rounded to
+/// `DISPLAY_DIGITS` places
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Integer(num) => write!(f, "{}", num),
+            Number::Float(num) => write!(f, "{}", num),
+            Number::Rational(num) => {
+                let rounded = num.round(DISPLAY_DIGITS);
+                let scale = BigInt::from(10).pow(DISPLAY_DIGITS as u32);
+                let digits = (&rounded * &Rational::from(scale)).floor();
+
+                let sign = if digits.is_negative() { "-" } else { "" };
+                let digits = format!(
+                    "{:0>width$}",
+                    digits.abs().to_string(),
+                    width = DISPLAY_DIGITS as usize + 1
+                );
+                let (whole, fraction) = digits.split_at(digits.len() - DISPLAY_DIGITS as usize);
+                match fraction.trim_end_matches('0') {
+                    "" => write!(f, "{}{}", sign, whole),
+                    fraction => write!(f, "{}{}.{}", sign, whole, fraction),
+                }
+            }
+        }
+    }
+}
+
+/// Numbers reach the front-end as plain JSON numbers
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl<'de> Deserialize<'de> for Number {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Number::Float)
+    }
+}
@@ -1,26 +1,135 @@
 use crate::ast::*;
+use crate::dates::{self, Calendar, SECONDS_PER_DAY, WEEKDAYS};
 use crate::defaults::ENVIRONMENT;
+use crate::errors::DedoError;
+use crate::bigint::BigInt;
+use crate::number::{NumberFormat, Rational};
+use crate::rates::RateProvider;
 use crate::runtime::evaluate;
 use crate::types::*;
+use crate::zones;
 use peg;
 use regex::Regex;
+use std::sync::Arc;
 
-peg::parser!(grammar dedo_parser() for str {
+peg::parser!(grammar dedo_parser(format: NumberFormat) for str {
     rule _() = quiet!{[' ' | '\t']*}
+    rule __() = quiet!{[' ' | '\t']+}
 
-    rule number() -> f64
-        = precedence! {
-            n:$("." ['0'..='9']+) { n.parse().unwrap() }
-            n:$(['0'..='9']+ "." ['0'..='9']*) { n.parse().unwrap() }
+    rule digits() = ['0'..='9']+ ("_" ['0'..='9']+)*
+    // inside a call's arguments commas separate the arguments, whatever the
+    // format, so `max(100,200)` is 200; parentheses group digits again, as
+    // in `max((1,000), 2)`
+    rule separator(in_call: bool) = "." / "," {? if in_call { Err("argument") } else { Ok(()) } }
+    rule exponent() = ['e' | 'E'] ['+' | '-']? ['0'..='9']+
 
-            --
+    // parsed exactly, with `format` telling the decimal separator from the
+    // thousands one; the environment decides whether to keep it exact
+    rule number(in_call: bool) -> Rational
+        = "0x" n:$((['0'..='9' | 'a'..='f' | 'A'..='F']+) ++ "_") { radix(n, 16) }
+        / "0o" n:$((['0'..='7']+) ++ "_") { radix(n, 8) }
+        / "0b" n:$((['0'..='1']+) ++ "_") { radix(n, 2) }
+        / n:$(
+            ['0'..='9']*<1,3> (separator(in_call) ['0'..='9']*<3> !['0'..='9'])+
+            (separator(in_call) digits())? exponent()?
+        ) {? format.parse(n).ok_or("number") }
+        / n:$(digits() (separator(in_call) digits()?)? exponent()?) {? format.parse(n).ok_or("number") }
+        / n:$(separator(in_call) digits() exponent()?) {? format.parse(n).ok_or("number") }
+        / n:$(digits()) {? format.parse(n).ok_or("number") }
 
-            n:$(['0'..='9']+) { n.parse().unwrap() }
+    // letters, including accented ones like `č` or `ł`, and currency symbols
+    rule ident_char() = [
+        'a'..='z' | 'A'..='Z' | 'À'..='Ö' | 'Ø'..='ö' | 'ø'..='ɏ' | 'µ' | 'μ' | '°' | '_' |
+        '£' | '$' | '€' | '¥' | '¢' | '฿' | '₠'..='⃏'
+    ]
+
+    rule ident() -> String = s:$(ident_char()+) {?
+        match s {
+            "as" | "of" | "off" | "on" => Err("identifier"),
+            _ => Ok(s.parse().unwrap()),
+        }
+    }
+
+    // a built-in unit of a few words like `nautical miles` or `fl oz`, or
+    // else a single word
+    rule unit() -> String
+        = u:$(ident() __ ident() __ ident()) {? known_unit(u) }
+        / u:$(ident() __ ident()) {? known_unit(u) }
+        / ident()
+
+    // `300k` and `£1.2M`, written right after the number, or `4 billion`
+    rule scale_suffix() -> u32 = "k" { 3 } / "M" { 6 } / "bn" { 9 }
+    rule scale_word() -> u32
+        = "thousand" { 3 } / "million" { 6 } / "billion" { 9 } / "trillion" { 12 }
+
+    // a scale is only a scale if it is a word of its own, so `km` and
+    // `kWh` stay units, and `K` is always kelvin
+    rule scaled_number(in_call: bool) -> Rational
+        = n:number(in_call) s:scale_suffix() !ident_char() { scale(n, s) }
+        / n:number(in_call) _ s:scale_word() !ident_char() { scale(n, s) }
+        / number(in_call)
+
+    // `2026-10-18`, as days since 1970-01-01
+    rule date() -> i64
+        = y:$(['0'..='9']*<4>) "-" m:$(['0'..='9']*<2>) "-" d:$(['0'..='9']*<2>) !['0'..='9'] {?
+            dates::date(y.parse().unwrap(), m.parse().unwrap(), d.parse().unwrap()).ok_or("date")
         }
 
-    rule ident() -> String = s:$(['a'..='z' | 'A'..='Z' | '£' | '$' | '€' | '_']+) { s.parse().unwrap() }
+    rule two_digits() -> i64 = n:$(['0'..='9']*<2>) { n.parse().unwrap() }
+    rule minutes() -> i64 = n:two_digits() {? if n < 60 { Ok(n) } else { Err("minutes") } }
+
+    // `3pm`, `11:30 am` or `14:30:05`, as seconds since midnight. A bare
+    // hour takes am or pm right after it, as `5 pm` is five picometres.
+    rule clock() -> i64
+        = h:$(['0'..='9']*<1,2>) m:(":" m:minutes() _ { m })? pm:("am" { false } / "pm" { true }) !ident_char() {?
+            match h.parse::<i64>().unwrap() {
+                h @ 1..=12 => {
+                    let h = h % 12 + if pm { 12 } else { 0 };
+                    Ok(h * 3600 + m.unwrap_or(0) * 60)
+                }
+                _ => Err("hour"),
+            }
+        }
+        / h:$(['0'..='9']*<1,2>) ":" m:minutes() s:(":" s:minutes() { s })? !['0'..='9'] {?
+            match h.parse::<i64>().unwrap() {
+                h @ 0..=23 => Ok(h * 3600 + m * 60 + s.unwrap_or(0)),
+                _ => Err("hour"),
+            }
+        }
+
+    rule weekday() -> u32 = i:ident() {?
+        let day = WEEKDAYS.iter().position(|day| day.eq_ignore_ascii_case(&i));
+        day.map(|day| day as u32).ok_or("weekday")
+    }
+
+    // `London`, `Europe/London`, `New_York` or `PST`
+    rule zone() -> String = z:$(ident() ++ "/") {?
+        zones::find(z).map(|_| z.to_string()).ok_or("time zone")
+    }
+
+    // `hours and minutes`, `ft and in`, or a clock like `h:m:s`
+    rule mixed_units() -> Term
+        = "h:m:s" !ident_char() { mixed(&["hour", "minute", "second"], Layout::Clock) }
+        / "h:m" !ident_char() { mixed(&["hour", "minute"], Layout::Clock) }
+        / "m:s" !ident_char() { mixed(&["minute", "second"], Layout::Clock) }
+        / u:(unit() **<2,> (_ ("," / "and") _)) { Term::Mixed(u, Layout::Words) }
+
+    rule amount(in_call: bool) -> Term
+        = n:scaled_number(in_call) _ i:unit() { Term::Binary(Box::new(Term::Num(n)), Op::Mul, Box::new(Term::Ident(i))) }
+
+    rule moment() -> Term
+        = d:date() (__ / "T") c:clock() { Term::Moment(Calendar::DateTime, d * SECONDS_PER_DAY + c) }
+        / d:date() { Term::Moment(Calendar::Date, d * SECONDS_PER_DAY) }
+        / c:clock() { Term::Moment(Calendar::Time, c) }
+        / "next" __ w:weekday() { Term::Weekday(1, w) }
+        / "last" __ w:weekday() { Term::Weekday(-1, w) }
+
+    rule term(in_call: bool) -> Term = precedence!{
+        x:(@) _ "as" _ ("a" _)? "%" _ "of" _ y:@ { Term::Binary(Box::new(x), Op::AsPercentOf, Box::new(y)) }
+        x:(@) _ "as" _ ("a" _)? "%" { Term::AsPercent(Box::new(x)) }
+
+        --
 
-    rule term() -> Term = precedence!{
         x:(@) _ "+" _ y:@ { Term::Binary(Box::new(x), Op::Add, Box::new(y)) }
         x:(@) _ "-" _ y:@ { Term::Binary(Box::new(x), Op::Sub, Box::new(y)) }
         "-" _ y:@ { Term::Negate(Box::new(y)) }
@@ -29,10 +138,22 @@ peg::parser!(grammar dedo_parser() for str {
 
         x:(@) _ "*" _ y:@ { Term::Binary(Box::new(x), Op::Mul, Box::new(y)) }
         x:(@) _ "/" _ y:@ { Term::Binary(Box::new(x), Op::Div, Box::new(y)) }
+        x:(@) _ "off" _ y:@ { Term::Binary(Box::new(x), Op::Off, Box::new(y)) }
+        x:(@) _ "of" _ y:@ { Term::Binary(Box::new(x), Op::Of, Box::new(y)) }
+        x:(@) _ "on" _ y:@ { Term::Binary(Box::new(x), Op::On, Box::new(y)) }
+
+        --
+
+        m:moment() __ z:zone() { Term::Zoned(Box::new(m), z) }
+        m:moment() { m }
 
         --
 
-        n:number() _ i:ident() _ "^" t:term() { 
+        a:(amount(in_call) **<2,> _) { Term::Compound(a) }
+
+        --
+
+        n:scaled_number(in_call) _ i:unit() _ "^" t:term(in_call) { 
             Term::Binary(
                 Box::new(Term::Num(n)), 
                 Op::Mul, 
@@ -42,7 +163,7 @@ peg::parser!(grammar dedo_parser() for str {
                 )
             ) 
         }
-        n:number() _ i:ident() _ "**" t:term() { 
+        n:scaled_number(in_call) _ i:unit() _ "**" t:term(in_call) { 
             Term::Binary(
                 Box::new(Term::Num(n)), 
                 Op::Mul, 
@@ -60,45 +181,170 @@ peg::parser!(grammar dedo_parser() for str {
 
         --
 
-        n:number() _ i:ident() { Term::Binary(Box::new(Term::Num(n)), Op::Mul, Box::new(Term::Ident(i))) }
-        i:ident() _ n:number() { Term::Binary(Box::new(Term::Num(n)), Op::Mul, Box::new(Term::Ident(i))) }
-        i:ident() { Term::Ident(i) }
+        i:ident() "(" _ args:(term(true) ** (_ "," _)) _ ")" { Term::Call(i, args) }
+        n:scaled_number(in_call) _ i:unit() { Term::Binary(Box::new(Term::Num(n)), Op::Mul, Box::new(Term::Ident(i))) }
+        i:ident() _ n:scaled_number(in_call) { Term::Binary(Box::new(Term::Num(n)), Op::Mul, Box::new(Term::Ident(i))) }
+        i:unit() { Term::Ident(i) }
 
         --
 
-        n:number() { Term::Num(n) }
-        "(" _ e:term() _ ")" { e }
+        n:scaled_number(in_call) _ "%" { Term::Percent(n) }
+        n:scaled_number(in_call) { Term::Num(n) }
+        "(" _ e:term(false) _ ")" { e }
     }
 
     pub rule statement() -> Statement = precedence!{
-        _ i:ident() _ "=" _ t:term() _ {  Statement::Assign(i, t) }
-        _ from:term() _ "to" _ to:term() _ { Statement::Transform(from, to) }
-        _ from:term() _ "in" _ to:term() _ { Statement::Transform(from, to) }
+        _ "unit" __ i:ident() _ "=" _ t:term(false) _ { Statement::DefineUnit(i, 1.0, t) }
+        _ n:number(false) _ i:ident() _ "=" _ t:term(false) _ { Statement::DefineUnit(i, n.to_f64(), t) }
+        _ i:ident() "(" _ params:(ident() ** (_ "," _)) _ ")" _ "=" _ t:term(false) _ { Statement::Define(i, params, t) }
+        _ i:ident() _ "=" _ t:term(false) _ {  Statement::Assign(i, t) }
+        _ from:term(false) _ "to" _ m:mixed_units() _ { Statement::Transform(from, m) }
+        _ from:term(false) _ "in" _ m:mixed_units() _ { Statement::Transform(from, m) }
+        _ from:term(false) _ "to" _ z:zone() _ { Statement::Transform(from, Term::Zone(z)) }
+        _ from:term(false) _ "in" _ z:zone() _ { Statement::Transform(from, Term::Zone(z)) }
+        _ from:term(false) _ "to" _ to:term(false) _ { Statement::Transform(from, to) }
+        _ from:term(false) _ "in" _ to:term(false) _ { Statement::Transform(from, to) }
 
         --
 
-        _ t:term() _ { Statement::Basic(t) }
+        _ t:term(false) _ { Statement::Basic(t) }
     }
 });
 
-pub fn parse_single<'input>(env: &'input mut Environment, input: &'input str) -> Result<Value, ()> {
-    match dedo_parser::statement(input) {
-        Ok(s) => evaluate(env, s),
-        _ => Err(()),
+/// A `0x`, `0o` or `0b` literal, `_` separators and all
+fn radix(digits: &str, radix: u32) -> Rational {
+    BigInt::from_str_radix(&digits.replace('_', ""), radix).unwrap().into()
+}
+
+/// `num` times `10^power`, for scales like `thousand`
+fn scale(num: Rational, power: u32) -> Rational {
+    &num * &Rational::from(BigInt::from(10).pow(power))
+}
+
+/// A unit of several words, with the spaces between them tidied up
+fn known_unit(words: &str) -> Result<String, &'static str> {
+    let name = words.split_whitespace().collect::<Vec<_>>().join(" ");
+    match ENVIRONMENT.units.id(&Unit::from(name.as_str())) {
+        Some(_) => Ok(name),
+        None => Err("unit"),
+    }
+}
+
+fn mixed(units: &[&str], layout: Layout) -> Term {
+    Term::Mixed(units.iter().map(|unit| unit.to_string()).collect(), layout)
+}
+
+/// The line without its `label:`, if any, leaving the colons of times
+/// like `14:30` and of clock layouts like `h:m:s` alone. Labels can have
+/// digits, so `Q1:500` is `500`.
+fn strip_label(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let digit = |i: usize| bytes.get(i).is_some_and(u8::is_ascii_digit);
+    // one or two digits of hours, on their own or after the `T` of a date,
+    // then two of minutes
+    let clock_time = |i: usize| {
+        let hours = bytes[..i].iter().rev().take_while(|b| b.is_ascii_digit()).count();
+        let start = i - hours;
+        let iso_date = start > 1 && bytes[start - 1] == b'T' && digit(start - 2);
+        let in_word = start > 0 && bytes[start - 1].is_ascii_alphabetic() && !iso_date;
+        (1..=2).contains(&hours) && !in_word && digit(i + 1) && digit(i + 2) && !digit(i + 3)
+    };
+    let clock_layout = |i: usize| {
+        let start = line[..i].rfind(char::is_whitespace).map_or(0, |start| start + 1);
+        let end = line[i..].find(char::is_whitespace).map_or(line.len(), |end| i + end);
+        matches!(&line[start..end], "h:m:s" | "h:m" | "m:s")
+    };
+    let label = (0..bytes.len()).rev().find(|&i| {
+        bytes[i] == b':' && !clock_time(i) && !clock_layout(i)
+    });
+
+    match label {
+        Some(i) => &line[i + 1..],
+        None => line,
     }
 }
 
-pub fn parse<'input>(input: &'input str) -> Vec<Result<Value, ()>> {
+/// A number like `1,250` on its own, which could have either separator
+fn ambiguous_number(input: &str) -> Option<String> {
     lazy_static! {
-        static ref RE: Regex = Regex::new("^(.*:)?(.*)$").unwrap();
+        static ref RE: Regex =
+            Regex::new(r"(?:^|[^0-9.,_])([0-9]{1,3},[0-9]{3})(?:$|[^0-9.,_])").unwrap();
+    }
+
+    RE.captures(&outside_calls(input)).map(|captures| captures[1].to_string())
+}
+
+/// `input` with the arguments of calls like `max(100,200)` blanked out, as
+/// the commas there separate the arguments
+fn outside_calls(input: &str) -> String {
+    // whether each parenthesis still open is a call's
+    let mut calls = Vec::new();
+    let mut previous = ' ';
+    input
+        .chars()
+        .map(|c| {
+            let in_call = calls.last() == Some(&true);
+            let shown = match c {
+                '(' => {
+                    calls.push(previous.is_alphabetic() || previous == '_');
+                    c
+                }
+                ')' => {
+                    calls.pop();
+                    c
+                }
+                _ if in_call => ' ',
+                _ => c,
+            };
+            previous = c;
+            shown
+        })
+        .collect()
+}
+
+pub fn parse_single(env: &mut Environment, input: &str) -> Result<Value, DedoError> {
+    if input.trim().is_empty() {
+        return Err(DedoError::EmptyLine);
     }
+
+    if env.number_format() == NumberFormat::Auto {
+        if let Some(num) = ambiguous_number(input) {
+            return Err(DedoError::AmbiguousSeparator(num));
+        }
+    }
+
+    match dedo_parser::statement(input, env.number_format()) {
+        Ok(s) => evaluate(env, s).map(|value| {
+            env.stamp_rates(env.spell_units(env.round_to_minor_units(value)))
+        }),
+        Err(e) => Err(e.into()),
+    }
+}
+
+pub fn parse(input: &str) -> Vec<Result<Value, DedoError>> {
+    parse_in(ENVIRONMENT.clone(), input)
+}
+
+/// Parses `input` converting currencies with the rates of `provider`
+/// instead of the built-in ones
+pub fn parse_with_rates(
+    input: &str,
+    provider: Arc<dyn RateProvider>,
+) -> Vec<Result<Value, DedoError>> {
+    let mut env = ENVIRONMENT.clone();
+    env.set_rate_provider(provider);
+    parse_in(env, input)
+}
+
+/// Parses `input` line by line in `env`, e.g. one doing float
+/// arithmetic
+pub fn parse_in(mut env: Environment, input: &str) -> Vec<Result<Value, DedoError>> {
     // parse the input line by line
     let lines: Vec<&str> = input.split("\n").collect();
-    let mut env = ENVIRONMENT.clone();
     let mut res = Vec::new();
     for line in lines.iter() {
-        let filtered_line = RE.captures(line).unwrap().get(2).unwrap().as_str();
-        let val: Result<Value, ()> = parse_single(&mut env, filtered_line);
+        let filtered_line = strip_label(line);
+        let val: Result<Value, DedoError> = parse_single(&mut env, filtered_line);
         res.push(val.clone());
         env.add_entry(val);
     }
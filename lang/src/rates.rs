@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use regex::Regex;
+use serde::Deserialize;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::JsValue;
+use crate::errors::DedoError;
+
+/// A source of currency exchange rates, all quoted against one base
+/// currency and keyed by ISO 4217 code
+pub trait RateProvider: fmt::Debug + Send + Sync {
+    /// The currency every rate is quoted against, e.g. `EUR` for the ECB
+    fn base(&self) -> &str;
+
+    /// How many units of `currency` one unit of the base currency buys
+    fn rate(&self, currency: &str) -> Option<f64>;
+
+    /// When the rates were published, e.g. `2024-05-17`
+    fn timestamp(&self) -> Option<String>;
+
+    /// How many units of `to` one unit of `from` buys
+    fn exchange(&self, from: &str, to: &str) -> Option<f64> {
+        let quote = |currency: &str| {
+            if currency == self.base() {
+                Some(1.0)
+            } else {
+                self.rate(currency)
+            }
+        };
+
+        Some(quote(to)? / quote(from)?)
+    }
+}
+
+/// A shared rate provider. Environments are only equal if they share the
+/// very same provider, since a provider may be backed by the host.
+#[derive(Clone, Debug)]
+pub struct Rates(pub Arc<dyn RateProvider>);
+
+impl PartialEq for Rates {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// A fixed table of rates, either written out or loaded from a file
+#[derive(Clone, Debug, PartialEq)]
+pub struct StaticRates {
+    base: String,
+    rates: HashMap<String, f64>,
+    timestamp: Option<String>,
+}
+
+/// The `{"base": "EUR", "date": "2024-05-17", "rates": {"USD": 1.0844}}`
+/// shape most rate APIs answer with
+#[derive(Deserialize)]
+struct JsonRates {
+    base: String,
+    date: Option<String>,
+    rates: HashMap<String, f64>,
+}
+
+impl StaticRates {
+    pub fn new<S: Into<String>>(base: S) -> StaticRates {
+        StaticRates {
+            base: base.into(),
+            rates: HashMap::new(),
+            timestamp: None,
+        }
+    }
+
+    pub fn with_rate<S: Into<String>>(mut self, currency: S, rate: f64) -> StaticRates {
+        self.rates.insert(currency.into(), rate);
+        self
+    }
+
+    pub fn with_timestamp<S: Into<String>>(self, timestamp: S) -> StaticRates {
+        StaticRates {
+            timestamp: Some(timestamp.into()),
+            ..self
+        }
+    }
+
+    /// Reads a rates file, picking the format from its extension: `.json`,
+    /// or the ECB's `.csv` and `.xml` reference rates
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<StaticRates, DedoError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|error| DedoError::InvalidRates(error.to_string()))?;
+
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => StaticRates::from_json(&contents),
+            Some("csv") => StaticRates::from_ecb_csv(&contents),
+            Some("xml") => StaticRates::from_ecb_xml(&contents),
+            _ => Err(DedoError::InvalidRates(format!(
+                "unknown rates file format `{}`",
+                path.display()
+            ))),
+        }
+    }
+
+    pub fn from_json(contents: &str) -> Result<StaticRates, DedoError> {
+        let json: JsonRates = serde_json::from_str(contents)
+            .map_err(|error| DedoError::InvalidRates(error.to_string()))?;
+
+        Ok(StaticRates {
+            base: json.base,
+            rates: json.rates,
+            timestamp: json.date,
+        })
+    }
+
+    /// The ECB's `eurofxref.csv`: a header row of currencies after `Date`,
+    /// and a single row of rates against the euro
+    pub fn from_ecb_csv(contents: &str) -> Result<StaticRates, DedoError> {
+        let mut rows = contents.lines().filter(|line| !line.trim().is_empty()).map(|line| {
+            line.split(',').map(str::trim).filter(|cell| !cell.is_empty()).collect::<Vec<_>>()
+        });
+
+        let (header, values) = match (rows.next(), rows.next()) {
+            (Some(header), Some(values)) if header.first() == Some(&"Date") => (header, values),
+            _ => return Err(DedoError::InvalidRates("expected a `Date` header row".into())),
+        };
+
+        let mut rates = StaticRates::new("EUR");
+        for (currency, rate) in header.iter().zip(values.iter()).skip(1) {
+            // discontinued currencies are listed as `N/A`
+            if let Ok(rate) = rate.parse() {
+                rates = rates.with_rate(*currency, rate);
+            }
+        }
+
+        match values.first() {
+            Some(date) => Ok(rates.with_timestamp(*date)),
+            None => Ok(rates),
+        }
+    }
+
+    /// The ECB's `eurofxref-daily.xml`, with one
+    /// `<Cube currency='USD' rate='1.0844'/>` per currency
+    pub fn from_ecb_xml(contents: &str) -> Result<StaticRates, DedoError> {
+        lazy_static! {
+            static ref TIME: Regex = Regex::new(r#"time=["']([^"']+)["']"#).unwrap();
+            static ref RATE: Regex =
+                Regex::new(r#"currency=["']([A-Z]{3})["']\s+rate=["']([0-9.]+)["']"#).unwrap();
+        }
+
+        let mut rates = StaticRates::new("EUR");
+        for captures in RATE.captures_iter(contents) {
+            let rate = captures[2]
+                .parse()
+                .map_err(|_| DedoError::InvalidRates(format!("invalid rate `{}`", &captures[2])))?;
+            rates = rates.with_rate(&captures[1], rate);
+        }
+
+        if rates.rates.is_empty() {
+            return Err(DedoError::InvalidRates("no rates found".into()));
+        }
+
+        match TIME.captures(contents) {
+            Some(captures) => Ok(rates.with_timestamp(&captures[1])),
+            None => Ok(rates),
+        }
+    }
+}
+
+impl RateProvider for StaticRates {
+    fn base(&self) -> &str {
+        &self.base
+    }
+
+    fn rate(&self, currency: &str) -> Option<f64> {
+        self.rates.get(currency).copied()
+    }
+
+    fn timestamp(&self) -> Option<String> {
+        self.timestamp.clone()
+    }
+}
+
+/// Rates looked up through a JavaScript function `(currency) => rate`,
+/// e.g. one reading a cache the front-end keeps up to date. Only built for
+/// the browser, where nothing can be shared between threads.
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+pub struct HostRates {
+    base: String,
+    timestamp: Option<String>,
+    callback: js_sys::Function,
+}
+
+// wasm32 without threads is single threaded, so the callback is never
+// actually shared or sent between threads
+#[cfg(target_arch = "wasm32")]
+unsafe impl Send for HostRates {}
+#[cfg(target_arch = "wasm32")]
+unsafe impl Sync for HostRates {}
+
+#[cfg(target_arch = "wasm32")]
+impl HostRates {
+    pub fn new(base: String, timestamp: Option<String>, callback: js_sys::Function) -> HostRates {
+        HostRates {
+            base,
+            timestamp,
+            callback,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl RateProvider for HostRates {
+    fn base(&self) -> &str {
+        &self.base
+    }
+
+    fn rate(&self, currency: &str) -> Option<f64> {
+        self.callback
+            .call1(&JsValue::NULL, &JsValue::from_str(currency))
+            .ok()?
+            .as_f64()
+    }
+
+    fn timestamp(&self) -> Option<String> {
+        self.timestamp.clone()
+    }
+}
@@ -1,43 +1,181 @@
 use super::ast::*;
+use super::errors::DedoError;
+use super::functions::UserFunction;
+use super::number::Number;
 use super::types::*;
 
-pub fn evaluate<'a>(env: &'a mut Environment, statement: Statement) -> Result<Value, ()> {
+pub fn evaluate(env: &mut Environment, statement: Statement) -> Result<Value, DedoError> {
     match statement {
         Statement::Assign(ident, term) => {
             let t = evaluate_term(env, term);
             env.assign(ident, t?)
         },
+        Statement::Define(name, params, body) => {
+            let defined = Value::function(&name, &params);
+            env.define_function(name, params, body);
+            Ok(defined)
+        },
+        Statement::DefineUnit(unit, amount, term) => {
+            let value = evaluate_term(env, term);
+            env.define_unit(unit, amount, value?)
+        },
+        Statement::Transform(from, Term::Zone(zone)) => {
+            let value = evaluate_term(env, from);
+            env.convert_zone(value?, zone)
+        },
+        Statement::Transform(from, Term::Mixed(units, layout)) => {
+            let value = evaluate_term(env, from);
+            env.convert_mixed(value?, units, layout)
+        },
         Statement::Transform(from, to) => {
             let left = evaluate_term(env, from);
             let right = evaluate_term(env, to);
-            Ok(env.convert(
+            env.convert(
                 left?,
                 right?,
-            ))
+            )
         },
         Statement::Basic(term) => evaluate_term(env, term),
     }
 }
 
-pub fn evaluate_term<'a>(env: &'a mut Environment, term: Term) -> Result<Value, ()> {
+pub fn evaluate_term(env: &mut Environment, term: Term) -> Result<Value, DedoError> {
     match term {
+        // a date moves by each part in turn, so the months of `today + 1
+        // month 2 days` are calendar ones
+        Term::Binary(box left, op @ (Op::Add | Op::Sub), box Term::Compound(terms)) => {
+            let left_value = evaluate_term(env, left)?;
+            let parts = evaluate_terms(env, terms)?;
+            if left_value.calendar().is_none() {
+                let right_value = env.compound(parts)?;
+                return evaluate_binary(env, left_value, op, right_value);
+            }
+
+            parts
+                .into_iter()
+                .try_fold(left_value, |moment, part| evaluate_binary(env, moment, op.clone(), part))
+        },
         Term::Binary(box left, op, box right) => {
             let left_value = evaluate_term(env, left);
             let right_value = evaluate_term(env, right);
 
-            match op {
-                Op::Add => Ok(env.add(left_value?, right_value?)),
-                Op::Sub => Ok(env.sub(left_value?, right_value?)),
-                Op::Mul => Ok(env.mul(left_value?, right_value?)),
-                Op::Div => Ok(env.div(left_value?, right_value?)),
-                Op::Pow => Ok(env.pow(left_value?, right_value?)),
-            }
+            evaluate_binary(env, left_value?, op, right_value?)
         },
         Term::Negate(box term) => {
             let value = evaluate_term(env, term);
             Ok(Value::negate(value?))
         },
+        Term::AsPercent(box term) => {
+            let value = evaluate_term(env, term)?;
+            if value.is_percent() {
+                Ok(value)
+            } else if value.is_unitless() {
+                Ok(Value::percent(value.number() * Number::integer(100)))
+            } else {
+                Err(DedoError::IncompatibleUnits(value.units().to_string(), "%".into()))
+            }
+        },
         Term::Ident(ident) => env.ident(ident),
-        Term::Num(num) => Ok(Value::unitless(num)),
+        Term::Call(name, args) => {
+            let args = evaluate_terms(env, args)?;
+            match env.user_function(&name) {
+                Some(function) => call_user_function(env, &name, function, args),
+                None => env.call(&name, args),
+            }
+        },
+        Term::Num(num) => Ok(Value::unitless(env.number(num))),
+        Term::Percent(num) => Ok(Value::percent(env.number(num))),
+        Term::Moment(calendar, seconds) => Ok(Value::moment(calendar, Number::integer(seconds))),
+        Term::Weekday(direction, weekday) => Ok(env.weekday(direction, weekday)),
+        Term::Zoned(box term, zone) => {
+            let value = evaluate_term(env, term);
+            env.zoned(value?, zone)
+        },
+        Term::Compound(terms) => {
+            let values = evaluate_terms(env, terms)?;
+            env.compound(values)
+        },
+        Term::Zone(zone) => env.ident(zone),
+        Term::Mixed(units, _) => env.ident(units[0].clone()),
+    }
+}
+
+fn evaluate_terms(env: &mut Environment, terms: Vec<Term>) -> Result<Vec<Value>, DedoError> {
+    terms.into_iter().map(|term| evaluate_term(env, term)).collect()
+}
+
+/// Evaluates the body of a document function with its parameters bound,
+/// restoring the caller's variables afterwards
+fn call_user_function(
+    env: &mut Environment,
+    name: &str,
+    function: UserFunction,
+    args: Vec<Value>,
+) -> Result<Value, DedoError> {
+    let arity = function.params.len();
+    if args.len() != arity {
+        return Err(DedoError::WrongArgumentCount {
+            function: name.into(),
+            min: arity,
+            max: Some(arity),
+            got: args.len(),
+        });
+    }
+
+    env.enter_call(name)?;
+    let outer_vars = env.replace_vars(function.scope(args));
+    let result = evaluate_term(env, function.body);
+    env.replace_vars(outer_vars);
+    env.exit_call();
+
+    result
+}
+
+/// Percentages apply relative to the other operand, the way people expect
+/// in a notepad calculator: `£300 + 15%` is `£345` and `£300 * 15%` is
+/// `£45`, just like `2 * 15%` is `0.3`
+fn evaluate_binary(
+    env: &Environment,
+    left: Value,
+    op: Op,
+    right: Value,
+) -> Result<Value, DedoError> {
+    match op {
+        Op::Add if right.is_percent() && !left.is_percent() => {
+            let change = env.mul(left.clone(), right.fraction())?;
+            env.add(left, change)
+        },
+        Op::Sub if right.is_percent() && !left.is_percent() => {
+            let change = env.mul(left.clone(), right.fraction())?;
+            env.sub(left, change)
+        },
+        Op::Add => env.add(left, right),
+        Op::Sub => env.sub(left, right),
+        Op::Mul => match (left.is_percent(), right.is_percent()) {
+            (true, true) => env.mul(left, right.fraction()),
+            (true, false) => env.mul(left.fraction(), right),
+            (false, true) => env.mul(left, right.fraction()),
+            (false, false) => env.mul(left, right),
+        },
+        Op::Div if right.is_percent() && !left.is_percent() => env.div(left, right.fraction()),
+        Op::Div => env.div(left, right),
+        Op::Pow => env.pow(left, right),
+        Op::Of | Op::Off | Op::On if !left.is_percent() => {
+            Err(DedoError::NotAPercentage(left.units().to_string()))
+        },
+        Op::Of => env.mul(right, left.fraction()),
+        Op::Off => evaluate_binary(env, right, Op::Sub, left),
+        Op::On => evaluate_binary(env, right, Op::Add, left),
+        Op::AsPercentOf => {
+            let ratio = env.div(left.clone(), right.clone())?;
+            if !ratio.is_unitless() {
+                return Err(DedoError::IncompatibleUnits(
+                    left.units().to_string(),
+                    right.units().to_string(),
+                ));
+            }
+
+            Ok(Value::percent(ratio.number() * Number::integer(100)))
+        },
     }
 }
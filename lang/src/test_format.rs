@@ -0,0 +1,174 @@
+#[cfg(test)]
+mod tests {
+    use super::super::format::*;
+    use super::super::number::Number;
+    use super::super::parser::parse;
+    use super::super::types::*;
+    use pretty_assertions::assert_eq;
+    use wasm_bindgen_test::*;
+
+    fn formatted(input: &str, options: &FormatOptions) -> Vec<String> {
+        parse(input)
+            .iter()
+            .map(|result| match result {
+                Ok(value) => format_value(value, options),
+                Err(err) => err.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn format_defaults() {
+        assert_eq!(
+            formatted(
+                "£2280\n-£5\n12.5 RON\n2 kr\n1000 JPY\n100 km / 2 h\n108 km/h\n1.5e9 m^2\n\
+                 3 kg * m / s^2 / K\n2 / s\n15%\n1/3\n0.000000000025 m\n1234567.891",
+                &FormatOptions::default()
+            ),
+            vec![
+                "£2,280.00",
+                "-£5.00",
+                "12.50 RON",
+                "2.00 kr",
+                "1,000 JPY",
+                "50 km/h",
+                "108 km/h",
+                "1.5 × 10⁹ m²",
+                "3 kg·m/(K·s²)",
+                "2 s⁻¹",
+                "15%",
+                "0.3333333333",
+                "2.5 × 10⁻¹¹ m",
+                "1,234,567.891",
+            ]
+        );
+
+        // currencies are found whichever case their code is written in
+        assert_eq!(
+            formatted("12.5 usd\n1 usd / 3\n2 pen", &FormatOptions::default()),
+            vec!["12.50 usd", "0.33 usd", "2 pen"]
+        );
+
+        assert_eq!(Value::simple(43.2, "km/h").to_string(), "43.2 km/h");
+        assert_eq!(Value::unitless(f64::NEG_INFINITY).to_string(), "-∞");
+        assert_eq!(Value::unitless(Number::integer(0)).to_string(), "0");
+        assert_eq!(
+            formatted("2026-10-18\n3pm\n2026-10-18 9am + 90 s", &FormatOptions::default()),
+            vec!["2026-10-18", "15:00", "2026-10-18 09:01:30"]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn format_options() {
+        let decimals = FormatOptions {
+            decimals: Some(1),
+            thousands_separator: None,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            formatted("£2280\n1/3\n9.96e9", &decimals),
+            vec!["£2280.0", "0.3", "1.0 × 10¹⁰"]
+        );
+
+        let figures = FormatOptions {
+            significant_figures: Some(3),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            formatted("43.21 km/h\n99.96\n1234567\n0.0012345\n$1.5", &figures),
+            vec!["43.2 km/h", "100", "1,230,000", "0.00123", "$1.50"]
+        );
+
+        let placement = FormatOptions {
+            symbol_placement: SymbolPlacement::Prefix,
+            ..FormatOptions::with_decimal_separator(',')
+        };
+        assert_eq!(
+            formatted("1234.5 RON\n1234.5 €", &placement),
+            vec!["RON 1.234,50", "€1.234,50"]
+        );
+
+        let suffix = FormatOptions {
+            symbol_placement: SymbolPlacement::Suffix,
+            scientific_exponent: 0,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            formatted("$3\n1099511627776 m", &suffix),
+            vec!["3.00 $", "1,099,511,627,776 m"]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn format_mixed_units() {
+        assert_eq!(
+            formatted(
+                "5400 s in hours and minutes\n8130 s in hours, minutes and seconds\n\
+                 100 min in h and min\n1.8 m in m and cm\n1 day in hours and minutes\n\
+                 3600sec in h:m:s\n-5400 s in h:m\n90.5 s in m:s\nlap: 75 s in m:s\n\
+                 5 kg in hours and minutes\n-30 s in m:s\n-30 s in minutes and seconds\n\
+                 3599.99999999999 s in h:m:s\n1.99999999999999 hours in hours and minutes\n\
+                 30 s in hours, minutes and seconds\n0 s in minutes and seconds\n\
+                 90 s in seconds and minutes\n5 s in h and hours\n90 s in minutes and metres",
+                &FormatOptions::default()
+            ),
+            vec![
+                "1 hour 30 minutes",
+                "2 hours 15 minutes 30 seconds",
+                "1 h 40 min",
+                "1 m 80 cm",
+                "24 hours 0 minutes",
+                "01:00:00",
+                "-01:30",
+                "01:30.5",
+                "01:15",
+                "Cannot convert `kg` to `hours`",
+                "-00:30",
+                "-30 seconds",
+                "01:00:00",
+                "2 hours 0 minutes",
+                "30 seconds",
+                "0 seconds",
+                "Units go largest first, so `minutes` can't follow `seconds`",
+                "Units go largest first, so `hours` can't follow `h`",
+                "Cannot convert `s` to `metres`",
+            ]
+        );
+
+        // negating a split value leaves it in the last unit
+        assert_eq!(
+            formatted("90 min in h and min\n-prev", &FormatOptions::default())[1],
+            "-90 min"
+        );
+
+        // the value itself is kept in the last unit
+        let minutes = parse("5400 s in hours and minutes").remove(0).unwrap();
+        assert_eq!(minutes.number(), Number::integer(90));
+        assert_eq!(minutes.mixed().map(|mixed| mixed.layout), Some(Layout::Words));
+    }
+
+    /// Timed, so not run in the browser, which has no clock to time with.
+    /// These took up to a minute dividing by powers of ten over and over.
+    #[test]
+    fn format_big_numbers() {
+        let bound = if cfg!(debug_assertions) { 10.0 } else { 0.5 };
+        let start = std::time::Instant::now();
+        assert_eq!(
+            formatted(
+                "2^10000\nfactorial(3000)\n7 ^ 40000\n-1/3^20000\n-9.99999999996e20",
+                &FormatOptions::default()
+            ),
+            vec![
+                "1.9950631169 × 10³⁰¹⁰",
+                "4.1493596034 × 10⁹¹³⁰",
+                "8.3483484995 × 10³³⁸⁰³",
+                "-3.7575572547 × 10⁻⁹⁵⁴³",
+                "-1 × 10²¹",
+            ]
+        );
+        assert!(start.elapsed().as_secs_f64() < bound, "took {:?}", start.elapsed());
+    }
+}
\ No newline at end of file
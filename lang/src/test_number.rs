@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use super::super::bigint::BigInt;
+    use super::super::defaults::ENVIRONMENT;
+    use super::super::errors::DedoError;
+    use super::super::number::*;
+    use super::super::parser::{parse, parse_in};
+    use super::super::types::Value;
+    use pretty_assertions::assert_eq;
+    use wasm_bindgen_test::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    fn rational(s: &str) -> Rational {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn big_integers() {
+        let two_to_100 = "1267650600228229401496703205376";
+
+        assert_eq!(BigInt::from(2).pow(100).to_string(), two_to_100);
+        assert_eq!(big(two_to_100).to_f64(), 2f64.powi(100));
+        assert_eq!(big("-0"), BigInt::zero());
+        assert!("12a".parse::<BigInt>().is_err());
+
+        assert_eq!(
+            &big("18446744073709551615") + &BigInt::one(),
+            big("18446744073709551616")
+        );
+        assert_eq!(&BigInt::from(-5) - &big("99999999999"), big("-100000000004"));
+        assert_eq!(
+            &big("-123456789012345678901") * &big("1000000000000"),
+            big("-123456789012345678901000000000000")
+        );
+        assert_eq!(
+            big(two_to_100).div_rem(&BigInt::from(-7)),
+            (big("-181092942889747057356671886482"), big("2"))
+        );
+        assert_eq!(big("462").gcd(&big("-1071")), big("21"));
+
+        // dividing by and taking the gcd of numbers of several digits
+        let (three, two) = (BigInt::from(3).pow(300), BigInt::from(2).pow(200));
+        let (quotient, remainder) = (&three + &BigInt::from(7)).div_rem(&two);
+        assert_eq!(&(&quotient * &two) + &remainder, &three + &BigInt::from(7));
+        assert!(remainder < two);
+        assert_eq!((&three * &two).gcd(&(&two * &BigInt::from(-5).pow(81))), two);
+        assert_eq!(three.gcd(&two), BigInt::one());
+        assert!(big("-3") < big("2"));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn rationals() {
+        assert_eq!(rational("12.50"), Rational::new(BigInt::from(25), BigInt::from(2)));
+        assert_eq!(rational("-0.05").to_string(), "-1/20");
+        assert_eq!(Rational::from_f64(0.1), Some(rational(".1")));
+        assert_eq!(Rational::from_f64(f64::NAN), None);
+        assert!("1.2.3".parse::<Rational>().is_err());
+
+        assert_eq!(&rational("0.1") + &rational("0.2"), rational("0.3"));
+        assert_eq!(&rational("1") / &rational("3"), Rational::new(BigInt::one(), BigInt::from(3)));
+        assert_eq!(rational("2").pow(-3), Some(rational("0.125")));
+        assert_eq!(rational("0").recip(), None);
+        assert_eq!(rational("2.675").round(2), rational("2.68"));
+        assert_eq!(rational("-2.5").round(0), rational("-3"));
+        assert_eq!(rational("1250").round(-2), rational("1300"));
+        assert_eq!(rational("1.5").round(2_000_000_000), rational("1.5"));
+        assert_eq!(rational("1250").round(-2_000_000_000), rational("0"));
+        assert_eq!(rational("-2.5").floor(), BigInt::from(-3));
+        assert_eq!(rational("2.5").ceil(), BigInt::from(3));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn numbers() {
+        let exact = Number::literal(rational("0.1"), Arithmetic::Exact);
+        let float = Number::literal(rational("0.1"), Arithmetic::Float);
+
+        assert_eq!((exact.clone() + exact.clone() + exact.clone()).to_f64(), 0.3);
+        assert_eq!((float.clone() + float.clone() + float.clone()).to_f64(), 0.30000000000000004);
+        // a float anywhere makes the result a float
+        assert_eq!((exact.clone() + float + exact).to_f64(), 0.30000000000000004);
+
+        // fractions that come out whole are integers again
+        let third = Number::integer(1) / Number::integer(3);
+        assert!(matches!(third, Number::Rational(_)));
+        assert!(matches!(third * Number::integer(3), Number::Integer(_)));
+        assert_eq!(
+            (Number::integer(2) / Number::integer(3)).to_string(),
+            "0.6666666666666666666666666667"
+        );
+
+        // exact numbers too big to store fall back to floats
+        assert!(Number::integer(2).pow(1000).is_exact());
+        assert_eq!(Number::integer(10).pow(1_000_000).to_f64(), f64::INFINITY);
+        assert_eq!((Number::integer(1) / Number::integer(0)).to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn exact_arithmetic() {
+        let results = parse("2 ^ 100\n2 ^ 100 + 1 - 2 ^ 100\n0.1 + 0.2\n1/3 * 3\nfactorial(25)");
+
+        assert_eq!(
+            results[0].as_ref().map(|value| value.number().to_string()),
+            Ok("1267650600228229401496703205376".into())
+        );
+        assert_eq!(results[1], Ok(Value::unitless(Number::integer(1))));
+        assert_eq!(results[2], Ok(Value::unitless(0.3)));
+        assert_eq!(results[3], Ok(Value::unitless(Number::integer(1))));
+        assert_eq!(
+            results[4].as_ref().map(|value| value.number().to_string()),
+            Ok("15511210043330985984000000".into())
+        );
+
+        assert_eq!(
+            parse("factorial(2.5)\nfactorial(3 m)\nfloor(-2.5 m)\nsqrt(4)"),
+            vec![
+                Err(DedoError::NotAWholeNumber("2.5".into())),
+                Err(DedoError::IncompatibleUnits("m".into(), "".into())),
+                Ok(Value::simple(Number::integer(-3), "m")),
+                Ok(Value::unitless(2.0)),
+            ]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn float_arithmetic() {
+        let mut env = ENVIRONMENT.clone();
+        env.set_arithmetic(Arithmetic::Float);
+
+        assert_eq!(
+            parse_in(env, "0.1 + 0.2\nrent = 1234.1 RON\nrent * 3 - 0.3 RON\n2^100 + 1 - 2^100"),
+            vec![
+                Ok(Value::unitless(0.30000000000000004)),
+                Ok(Value::simple(1234.1, "RON")),
+                Ok(Value::simple(3702.0, "RON")),
+                Ok(Value::unitless(0.0)),
+            ]
+        );
+    }
+
+    /// Timed, so not run in the browser, which has no clock to time with.
+    /// These took minutes with bit by bit division.
+    #[test]
+    fn big_integer_speed() {
+        let bound = if cfg!(debug_assertions) { 10.0 } else { 0.5 };
+        let start = std::time::Instant::now();
+        let big = "factorial(5000)\n7 ^ 40000\n(3^60000 + 1) / (2^60000 + 5^40000)";
+        for value in parse(big) {
+            assert!(value.unwrap().number().is_exact());
+        }
+        assert_eq!(BigInt::from(7).pow(40000).to_string().len(), 33804);
+        assert!(start.elapsed().as_secs_f64() < bound, "took {:?}", start.elapsed());
+    }
+}
\ No newline at end of file
@@ -1,12 +1,15 @@
 #[cfg(test)]
 mod tests {
     use super::super::defaults::ENVIRONMENT;
-    use super::super::parser::{parse, parse_single};
+    use super::super::errors::DedoError;
+    use super::super::number::NumberFormat;
+    use super::super::parser::{parse, parse_in, parse_single};
     use super::super::types::{Unit, UnitSet, Value};
+    use super::super::ParseOptions;
     use pretty_assertions::assert_eq;
     use wasm_bindgen_test::*;
 
-    fn parse_helper(input: &'static str) -> Result<Value, ()> {
+    fn parse_helper(input: &'static str) -> Result<Value, DedoError> {
         parse_single(&mut ENVIRONMENT.clone(), input)
     }
 
@@ -63,6 +66,288 @@ mod tests {
             parse_helper("($1 + $1) ** (1 + 4 - 2)"),
             Ok(Value::new(8.0, units!("$" to 3))),
         );
+
+        assert_eq!(parse_helper("2 ^ 0.5"), Ok(Value::unitless(2f64.sqrt())));
+        assert_eq!(parse_helper("(16 m^2) ^ 0.5"), Ok(Value::simple(4.0, "m")));
+        assert_eq!(parse_helper("(m^2)^(1/2)"), Ok(Value::simple(1.0, "m")));
+        assert_eq!(parse_helper("(27 m^6)^(2/3)"), Ok(Value::new(9.0, units!("m" to 4))));
+        assert_eq!(
+            parse_helper("(m)^(1/3)"),
+            Err(DedoError::FractionalUnitPower("m".into()))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn number_literals() {
+        assert_eq!(parse_helper("1,250,000"), Ok(Value::unitless(1250000.0)));
+        assert_eq!(parse_helper("£1,200.50"), Ok(Value::simple(1200.5, "£")));
+        assert_eq!(parse_helper("1.250.000,5"), Ok(Value::unitless(1250000.5)));
+        assert_eq!(parse_helper("1_000 m"), Ok(Value::simple(1000.0, "m")));
+        assert_eq!(parse_helper("6.02e23"), Ok(Value::unitless(6.02e23)));
+        assert_eq!(parse_helper("1.5E-3"), Ok(Value::unitless(0.0015)));
+        assert_eq!(parse_helper("0xFF + 0b1010 + 0o17"), Ok(Value::unitless(280.0)));
+        assert_eq!(parse_helper("0xff_ff"), Ok(Value::unitless(65535.0)));
+        assert_eq!(parse_helper("max(1,2)"), Ok(Value::unitless(2.0)));
+
+        // commas in calls separate the arguments
+        assert_eq!(parse_helper("round(12.345,2)"), Ok(Value::unitless(12.35)));
+        assert_eq!(parse_helper("max(100,200)"), Ok(Value::unitless(200.0)));
+        assert_eq!(parse_helper("max(1,000, 2)"), Ok(Value::unitless(2.0)));
+        assert_eq!(parse_helper("max((1,000.5), 2)"), Ok(Value::unitless(1000.5)));
+
+        assert_eq!(
+            parse_helper("1,250"),
+            Err(DedoError::AmbiguousSeparator("1,250".into()))
+        );
+        assert_eq!(
+            parse_helper("max((1,250), 2)"),
+            Err(DedoError::AmbiguousSeparator("1,250".into()))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn scale_words() {
+        assert_eq!(parse_helper("£1.2M"), Ok(Value::simple(1200000.0, "£")));
+        assert_eq!(parse_helper("300k users"), Ok(Value::simple(300000.0, "users")));
+        assert_eq!(parse_helper("$4 billion"), Ok(Value::simple(4e9, "$")));
+        assert_eq!(parse_helper("2.5bn + 1 thousand"), Ok(Value::unitless(2500001000.0)));
+
+        // units that start like a scale, and kelvin, are still units
+        assert_eq!(parse_helper("10km"), Ok(Value::simple(10.0, "km")));
+        assert_eq!(parse_helper("5kWh"), Ok(Value::simple(5.0, "kWh")));
+        assert_eq!(parse_helper("300K"), Ok(Value::simple(300.0, "K")));
+        assert_eq!(parse_helper("3 millions"), Ok(Value::simple(3.0, "millions")));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn number_formats() {
+        let mut env = ENVIRONMENT.clone();
+        env.set_number_format(NumberFormat::DecimalPoint);
+        let results = parse_in(env, "1,250\n1.250\n1.250.000");
+        assert_eq!(results[0], Ok(Value::unitless(1250.0)));
+        assert_eq!(results[1], Ok(Value::unitless(1.25)));
+        assert_eq!(results[2].as_ref().map_err(|error| error.kind()), Err("Parse"));
+
+        let mut env = ENVIRONMENT.clone();
+        env.set_number_format(NumberFormat::DecimalComma);
+        assert_eq!(
+            parse_in(env, "1,250\n1.250\n1.250.000,5 €\nround((2,5))\nround(2,5)"),
+            vec![
+                Ok(Value::unitless(1.25)),
+                Ok(Value::unitless(1250.0)),
+                Ok(Value::simple(1250000.5, "€")),
+                Ok(Value::unitless(3.0)),
+                Ok(Value::unitless(2.0)),
+            ]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn parse_options() {
+        let options = r#"{ "decimal_separator": ",", "unit_selection": "best" }"#;
+        let options: ParseOptions = serde_json::from_str(options).unwrap();
+        assert_eq!(
+            parse_in(options.environment(), "1.250 cm\n1,5 m + 30 cm"),
+            vec![Ok(Value::simple(12.5, "m")), Ok(Value::simple(1.8, "m"))]
+        );
+        assert_eq!(options.format().thousands_separator, Some('.'));
+
+        let options: ParseOptions = serde_json::from_str(r#"{ "arithmetic": "float" }"#).unwrap();
+        assert_eq!(parse_in(options.environment(), "0.1 + 0.2")[0], Ok(Value::unitless(0.1 + 0.2)));
+        assert_eq!(options.format().decimal_separator, '.');
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn parse_errors() {
+        match parse_helper("2 +") {
+            Err(DedoError::Parse { column, expected }) => {
+                assert_eq!(column, 4);
+                assert!(expected.contains(&"\"(\"".to_string()));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        assert_eq!(parse_helper("   "), Err(DedoError::EmptyLine));
+        assert_eq!(parse_helper("1 / (2 - 2)"), Err(DedoError::DivisionByZero));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn parse_incompatible_units() {
+        assert_eq!(
+            parse_helper("1 kg + 3 m"),
+            Err(DedoError::IncompatibleUnits("m".into(), "kg".into())),
+        );
+        assert_eq!(
+            parse_helper("5 kg in usd"),
+            Err(DedoError::IncompatibleUnits("kg".into(), "usd".into())),
+        );
+        assert_eq!(
+            parse_helper("5 m in glarps"),
+            Err(DedoError::UnknownUnit("glarps".into())),
+        );
+        assert_eq!(
+            parse_helper("2 kg 3 m"),
+            Err(DedoError::IncompatibleUnits("kg".into(), "m".into())),
+        );
+        assert_eq!(
+            parse_helper("(1 m^2) in cm"),
+            Ok(Value::new(10000.0, units!("cm" to 2))),
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn parse_percentages() {
+        assert_eq!(
+            parse("salary = £4400\nsalary - 20%\ntax = 20%\ntax of salary"),
+            vec![
+                Ok(Value::simple(4400.0, "£")),
+                Ok(Value::simple(3520.0, "£")),
+                Ok(Value::percent(20.0)),
+                Ok(Value::simple(880.0, "£")),
+            ]
+        );
+        assert_eq!(
+            parse_helper("3 of £2000"),
+            Err(DedoError::NotAPercentage("".into())),
+        );
+        assert_eq!(parse("tax(x) = x * 20%\ntax(100)")[1], Ok(Value::unitless(20.0)));
+        assert_eq!(
+            parse_helper("£50 as a % of 2 kg"),
+            Err(DedoError::IncompatibleUnits("£".into(), "kg".into())),
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn parse_functions() {
+        let sine = parse_helper("sin(30 deg)").unwrap();
+        assert!((sine.num() - 0.5).abs() < 1e-9);
+        assert!(sine.is_unitless());
+
+        assert_eq!(
+            parse_helper("sqrt(2 m)"),
+            Err(DedoError::FractionalUnitPower("m".into())),
+        );
+        assert_eq!(
+            parse_helper("sin(2 kg)"),
+            Err(DedoError::IncompatibleUnits("kg".into(), "rad".into())),
+        );
+        assert_eq!(
+            parse_helper("frobnicate(1)"),
+            Err(DedoError::UnknownFunction("frobnicate".into())),
+        );
+        assert_eq!(
+            parse_helper("round(1, 2, 3)"),
+            Err(DedoError::WrongArgumentCount {
+                function: "round".into(),
+                min: 1,
+                max: Some(2),
+                got: 3,
+            }),
+        );
+        assert_eq!(
+            parse_helper("max(1 m, 2 s)"),
+            Err(DedoError::IncompatibleUnits("m".into(), "s".into())),
+        );
+
+        let outside = |function: &str, argument: &str| {
+            Err(DedoError::OutsideDomain {
+                function: function.into(),
+                argument: argument.into(),
+            })
+        };
+        assert_eq!(parse_helper("sqrt(-1)"), outside("sqrt", "-1"));
+        assert_eq!(parse_helper("ln(0)"), outside("ln", "0"));
+        assert_eq!(parse_helper("log(-10)"), outside("log", "-10"));
+        assert_eq!(parse_helper("log(8, 1)"), outside("log", "1"));
+        assert_eq!(parse_helper("cbrt(-8)"), Ok(Value::unitless(-2.0)));
+
+        // rounding to more decimals than a float holds leaves it as it is
+        assert_eq!(parse_helper("round(sqrt(2), 400)"), Ok(Value::unitless(2f64.sqrt())));
+        let huge = 2f64.sqrt() * 1e300;
+        let rounded = parse_helper("round(sqrt(2) * 10^300, 100)").unwrap();
+        assert!((rounded.num() - huge).abs() / huge < 1e-12);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn parse_user_functions() {
+        assert_eq!(
+            parse(
+                "tax(x) = x * 20%\ntax(£300)\ncommute(d) = d / (30 km/h)\ncommute(15 km) in min"
+            ),
+            vec![
+                Ok(Value::function("tax", &["x".into()])),
+                Ok(Value::simple(60.0, "£")),
+                Ok(Value::function("commute", &["d".into()])),
+                Ok(Value::simple(30.0, "min")),
+            ]
+        );
+        assert_eq!(
+            parse("hyp(a, b) = sqrt(a^2 + b^2)\nhyp(3 m, 4 m)\nb"),
+            vec![
+                Ok(Value::function("hyp", &["a".into(), "b".into()])),
+                Ok(Value::simple(5.0, "m")),
+                Ok(Value::simple(1.0, "b")),
+            ]
+        );
+
+        // definitions don't split the block `sum` and `prev` look at
+        assert_eq!(
+            parse("5\ntax(x) = x * 20%\ntax(100)\nsum\nhalf(x) = x / 2\nprev")[3..].to_vec(),
+            vec![
+                Ok(Value::unitless(25.0)),
+                Ok(Value::function("half", &["x".into()])),
+                Ok(Value::unitless(25.0)),
+            ]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn user_function_closures() {
+        // variables are captured when the function is defined
+        assert_eq!(
+            parse("rate = 2\ndouble(x) = x * rate\nrate = 3\ndouble(5)")[3],
+            Ok(Value::unitless(10.0)),
+        );
+        assert_eq!(
+            parse("f(x) = g(x)\ng(x) = f(x)\nf(1)\ng(2)")[2..].to_vec(),
+            vec![
+                Err(DedoError::RecursiveFunction("f".into())),
+                Err(DedoError::RecursiveFunction("g".into())),
+            ]
+        );
+        assert_eq!(
+            parse("tax(x) = x * 20%\ntax(1, 2)")[1],
+            Err(DedoError::WrongArgumentCount {
+                function: "tax".into(),
+                min: 1,
+                max: Some(1),
+                got: 2,
+            }),
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn aggregate_errors() {
+        assert_eq!(parse("prev"), vec![Err(DedoError::EmptyPrev)]);
+        assert_eq!(
+            parse("1\n\nsum\nprod"),
+            vec![
+                Ok(Value::unitless(1.0)),
+                Err(DedoError::EmptyLine),
+                Err(DedoError::EmptySum),
+                Err(DedoError::EmptyProd),
+            ]
+        );
     }
 
     // these are the tests generated at build time from the ./spec folder
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use super::super::errors::DedoError;
+    use super::super::parser::{parse, parse_with_rates};
+    use super::super::rates::*;
+    use super::super::types::Value;
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+    use wasm_bindgen_test::*;
+
+    const ECB_CSV: &str = "Date, USD, JPY, CYP, GBP, \n\
+        17 May 2024, 1.0844, 168.86, N/A, 0.8544, \n";
+
+    const ECB_XML: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<gesmes:Envelope>
+    <Cube>
+        <Cube time='2024-05-17'>
+            <Cube currency='USD' rate='1.0844'/>
+            <Cube currency='JPY' rate='168.86'/>
+            <Cube currency='GBP' rate='0.8544'/>
+        </Cube>
+    </Cube>
+</gesmes:Envelope>";
+
+    fn ecb_rates() -> StaticRates {
+        StaticRates::new("EUR")
+            .with_rate("USD", 1.0844)
+            .with_rate("JPY", 168.86)
+            .with_rate("GBP", 0.8544)
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn exchange() {
+        let rates = StaticRates::new("GBP").with_rate("EUR", 1.25).with_rate("USD", 1.5);
+
+        assert_eq!(rates.exchange("GBP", "EUR"), Some(1.25));
+        assert_eq!(rates.exchange("EUR", "GBP"), Some(0.8));
+        assert_eq!(rates.exchange("EUR", "USD"), Some(1.2));
+        assert_eq!(rates.exchange("EUR", "RON"), None);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn rate_files() {
+        let json = r#"{
+            "base": "EUR",
+            "date": "2024-05-17",
+            "rates": {"USD": 1.0844, "JPY": 168.86, "GBP": 0.8544}
+        }"#;
+        assert_eq!(
+            StaticRates::from_json(json),
+            Ok(ecb_rates().with_timestamp("2024-05-17"))
+        );
+        assert_eq!(
+            StaticRates::from_ecb_csv(ECB_CSV),
+            Ok(ecb_rates().with_timestamp("17 May 2024"))
+        );
+        assert_eq!(
+            StaticRates::from_ecb_xml(ECB_XML),
+            Ok(ecb_rates().with_timestamp("2024-05-17"))
+        );
+
+        assert_eq!(
+            StaticRates::from_ecb_csv("USD, JPY\n1.0844, 168.86").map_err(|error| error.kind()),
+            Err("InvalidRates")
+        );
+        assert_eq!(
+            StaticRates::from_json("{}").map_err(|error| error.kind()),
+            Err("InvalidRates")
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn provided_rates() {
+        let rates = Arc::new(ecb_rates().with_timestamp("2024-05-17"));
+
+        assert_eq!(
+            parse_with_rates("€100 in USD\n16886 JPY in €\n2 kg\n£1 in RON", rates),
+            vec![
+                Ok(Value::simple(108.44, "USD").with_rates_as_of("2024-05-17")),
+                Ok(Value::simple(100.0, "€").with_rates_as_of("2024-05-17")),
+                Ok(Value::simple(2.0, "kg")),
+                Err(DedoError::MissingRate("RON".into())),
+            ]
+        );
+
+        // the built-in rates have no timestamp
+        assert_eq!(parse("£1 in RON"), vec![Ok(Value::simple(5.07, "RON"))]);
+    }
+}
@@ -2,76 +2,201 @@ extern crate test;
 
 #[cfg(test)]
 mod tests {
+    use super::super::currencies;
+    use super::super::dates::Calendar;
     use super::super::defaults::*;
+    use super::super::errors::DedoError;
+    use super::super::number::Number;
+    use super::super::parser::parse_in;
     use super::super::types::*;
+    use super::super::units::*;
     use super::test::Bencher;
-    use std::collections::HashMap;
     use wasm_bindgen_test::*;
 
     fn get_env() -> Environment {
         environment![
-            "km" to "m" is 1000.0,
-            "m" to "cm" is 100.0,
-            "h" to "min" is 60.0,
-            "min" to "s" is 60.0
+            Length: "m" {
+                "km" is 1000.0,
+                "cm" is 0.01
+            },
+            Time: "s" {
+                "h" is 3600.0,
+                "min" is 60.0
+            }
         ]
     }
 
     #[test]
     #[wasm_bindgen_test]
     fn environment_creation() {
-        let mut map = HashMap::new();
-        map.insert(("km".into(), "m".into()), 1000.0);
-        map.insert(("m".into(), "cm".into()), 100.0);
-        map.insert(("h".into(), "min".into()), 60.0);
-        map.insert(("min".into(), "s".into()), 60.0);
+        let mut table = UnitTable::default();
+        table.define_canonical("m", Dimension::Length, Prefixes::None);
+        table.define("km", UnitDef::new(Dimension::Length, 1000.0));
+        table.define("cm", UnitDef::new(Dimension::Length, 0.01));
+        table.define_canonical("s", Dimension::Time, Prefixes::None);
+        table.define("h", UnitDef::new(Dimension::Time, 3600.0));
+        table.define("min", UnitDef::new(Dimension::Time, 60.0));
 
-        assert_eq!(get_env(), Environment::new(&Conversions(map)));
+        assert_eq!(get_env(), Environment::new(table));
     }
 
     #[test]
     #[wasm_bindgen_test]
     fn environment_size() {
-        // all self, mirror and transitive
-        // dependencies should be there
-        assert_eq!(get_env().conversions.0.len(), 18);
+        // one entry per unit, no matter how many
+        // units share a dimension
+        assert_eq!(get_env().units.len(), 6);
 
-        // combinatorial explosion!
-        assert_eq!(ENVIRONMENT.conversions.0.len(), 555);
+        // the shared defaults are checked for what they hold rather than
+        // how much, which grows as units are added
+        let units = &ENVIRONMENT.units;
+        for unit in ["m", "km", "hours", "°C", "MiB", "nmi", "fl oz", "USD", "usd", "£"].iter() {
+            assert!(units.knows(&Unit::from(*unit)), "`{}` is not defined", unit);
+        }
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn dimensions() {
+        let env = get_env();
+        let speed = env.units.resolve(&units!("km" to 1, "h" to -1));
+
+        let mut expected = Dimensions::from(Dimension::Length);
+        expected.0.insert(Dimension::Time, -1);
+        assert_eq!(speed.dimensions, expected);
+        assert_eq!(speed.factor, 1000.0 / 3600.0);
+
+        assert_eq!(
+            env.units.get(&"glarp".into()),
+            UnitDef::new(Dimension::Opaque("glarp".into()), 1.0)
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn prefixes() {
+        let env = environment![
+            Length: "m" [Si] {},
+            Time: "s" [Si] {
+                "min" is 60
+            },
+            Data: "B" [SiAndBinary] {}
+        ];
+        let factor = |unit: &str| env.units.get(&unit.into()).factor;
+
+        assert_eq!(factor("km"), 1000.0);
+        assert_eq!(factor("kilom"), 1000.0);
+        assert_eq!(factor("µm"), 1e-6);
+        assert_eq!(factor("ms"), 0.001);
+        assert_eq!(factor("dam"), 10.0);
+        assert_eq!(factor("MiB"), 1048576.0);
+        assert_eq!(factor("GB"), 1e9);
+
+        // exact names win over prefixes
+        assert_eq!(factor("min"), 60.0);
+        assert_eq!(factor("m"), 1.0);
+
+        // binary prefixes and unprefixable units stay opaque
+        assert_eq!(
+            env.units.get(&"Kis".into()).dimensions,
+            Dimensions::from(Dimension::Opaque("Kis".into()))
+        );
+        assert_eq!(
+            env.units.get(&"kmin".into()).dimensions,
+            Dimensions::from(Dimension::Opaque("kmin".into()))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn temperatures() {
+        let env = environment![
+            Temperature: "K" {
+                "°C" offset 273.15 is 1,
+                "°F" offset (459.67 * 5.0 / 9.0) is 5.0 / 9.0
+            }
+        ];
+        let assert_close = |value: Result<Value, DedoError>, expected: Value| {
+            let value = value.unwrap();
+            assert_eq!(value.units(), expected.units());
+            assert!((value.num() - expected.num()).abs() < 1e-9, "{:?} != {:?}", value, expected);
+        };
+
+        // absolute temperatures are shifted
+        assert_close(
+            env.convert(Value::simple(20., "°C"), Value::simple(1., "°F")),
+            Value::simple(68., "°F"),
+        );
+        assert_close(
+            env.convert(Value::simple(-40., "°F"), Value::simple(1., "°C")),
+            Value::simple(-40., "°C"),
+        );
+        assert_close(
+            env.convert(Value::simple(0., "°C"), Value::simple(1., "K")),
+            Value::simple(273.15, "K"),
+        );
+
+        // differences are only scaled
+        assert_close(
+            env.sub(Value::simple(30., "°C"), Value::simple(20., "°C")),
+            Value::simple(10., "K"),
+        );
+        assert_close(
+            env.sub(Value::simple(50., "°F"), Value::simple(10., "°C")),
+            Value::simple(0., "K"),
+        );
+        assert_close(
+            env.add(Value::simple(20., "°C"), Value::simple(9., "°F")),
+            Value::simple(25., "°C"),
+        );
+        assert_close(
+            env.sub(Value::simple(20., "°C"), Value::simple(5., "K")),
+            Value::simple(15., "°C"),
+        );
     }
 
     #[bench]
     fn environment_bench(b: &mut Bencher) {
         b.iter(|| {
             environment![
-               "gbp" to "£" is 1,
-               "usd" to "$" is 1,
-               "eur" to "€" is 1,
-               "km" to "m" is 1000,
-               "m" to "dm" is 10,
-               "dm" to "cm" is 10,
-               "cm" to "mm" is 10,
-               "kg" to "g" is 1000,
-               "g" to "mg" is 1000,
-               "minute" to "second" is 60,
-               "hour" to "minute" is 60,
-               "day" to "hour" is 24,
-               "week" to "day" is 7,
-               "month" to "day" is 30.5,
-               "year" to "month" is 12,
-               "minute" to "minutes" is 1,
-               "hour" to "hours" is 1,
-               "day" to "days" is 1,
-               "week" to "weeks" is 1,
-               "month" to "months" is 1,
-               "year" to "years" is 1,
-               "s" to "second" is 1,
-               "sec" to "second" is 1,
-               "min" to "minute" is 1,
-               "h" to "hour" is 1,
-               "d" to "day" is 1,
-               "w" to "week" is 1,
-               "y" to "year" is 1
+                Currency: "gbp" {
+                    "£" is 1,
+                    "usd" is 1.0 / 1.20,
+                    "$" is 1.0 / 1.20,
+                    "eur" is 1.0 / 1.06,
+                    "€" is 1.0 / 1.06
+                },
+                Length: "m" {
+                    "km" is 1000,
+                    "dm" is 0.1,
+                    "cm" is 0.01,
+                    "mm" is 0.001
+                },
+                Mass: "kg" {
+                    "g" is 0.001,
+                    "mg" is 0.000001
+                },
+                Time: "second" {
+                    "s" is 1,
+                    "sec" is 1,
+                    "minute" is 60,
+                    "minutes" is 60,
+                    "min" is 60,
+                    "hour" is 3600,
+                    "hours" is 3600,
+                    "h" is 3600,
+                    "day" is 86400,
+                    "days" is 86400,
+                    "d" is 86400,
+                    "week" is 604800,
+                    "weeks" is 604800,
+                    "w" is 604800,
+                    "month" is 2635200,
+                    "months" is 2635200,
+                    "year" is 31622400,
+                    "years" is 31622400,
+                    "y" is 31622400
+                }
             ]
         })
     }
@@ -134,7 +259,7 @@ mod tests {
                 "h" to -1
             ),
         );
-        assert_eq!(env.add(left, right), expected);
+        assert_eq!(env.add(left, right), Ok(expected));
     }
 
     #[test]
@@ -162,7 +287,59 @@ mod tests {
                 "h" to -1
             ),
         );
-        assert_eq!(env.sub(left, right), expected);
+        assert_eq!(env.sub(left, right), Ok(expected));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn incompatible_units() {
+        let env = get_env();
+
+        assert_eq!(
+            env.add(Value::simple(1., "km"), Value::simple(3., "h")),
+            Err(DedoError::IncompatibleUnits("h".into(), "km".into()))
+        );
+        assert_eq!(
+            env.sub(
+                Value::new(1., units!("km" to 1, "h" to -1)),
+                Value::simple(3., "m")
+            ),
+            Err(DedoError::IncompatibleUnits("m".into(), "km/h".into()))
+        );
+        assert_eq!(
+            env.convert(Value::simple(5., "km"), Value::simple(1., "s")),
+            Err(DedoError::IncompatibleUnits("km".into(), "s".into()))
+        );
+        assert_eq!(
+            env.convert(Value::simple(5., "km"), Value::unitless(1.)),
+            Err(DedoError::IncompatibleUnits("km".into(), "".into()))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn unitless_operands() {
+        let env = get_env();
+
+        assert_eq!(
+            env.add(Value::simple(5., "km"), Value::unitless(3.)),
+            Ok(Value::simple(8., "km"))
+        );
+        assert_eq!(
+            env.sub(Value::unitless(5.), Value::simple(3., "km")),
+            Ok(Value::simple(2., "km"))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn units_display() {
+        assert_eq!(units!("km" to 1, "h" to -1).to_string(), "km/h");
+        assert_eq!(units!("s" to -2).to_string(), "1/s^2");
+        assert_eq!(
+            units!("m" to 2, "s" to -1, "kg" to -1).to_string(),
+            "m^2/(kg*s)"
+        );
     }
 
     #[test]
@@ -189,7 +366,7 @@ mod tests {
                 "km" to 2
             ),
         );
-        assert_eq!(env.mul(left, right), expected);
+        assert_eq!(env.mul(left, right), Ok(expected));
     }
 
     #[test]
@@ -216,11 +393,296 @@ mod tests {
                 "h" to -2
             ),
         );
-        assert_eq!(env.div(left, right), expected);
+        assert_eq!(env.div(left, right), Ok(expected));
 
         assert_eq!(
             env.div(Value::unitless(2.0), Value::unitless(2.0)),
-            Value::unitless(1.0)
+            Ok(Value::unitless(1.0))
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn dates() {
+        let mut env = ENVIRONMENT.clone();
+        // Sunday 2026-10-18 09:15
+        env.set_clock(1_792_314_900);
+        let day = |days: i64| Value::moment(Calendar::Date, Number::integer(days * 86_400));
+
+        assert_eq!(
+            parse_in(
+                env,
+                "now\ntoday\ntomorrow\nyesterday\nnext friday\nlast sunday\nnext sunday\n\
+                 today + 3 weeks\n2026-12-25 - today in days\nnow - today"
+            ),
+            vec![
+                Ok(Value::moment(Calendar::DateTime, Number::integer(1_792_314_900))),
+                Ok(day(20_744)),
+                Ok(day(20_745)),
+                Ok(day(20_743)),
+                Ok(day(20_749)),
+                Ok(day(20_737)),
+                Ok(day(20_751)),
+                Ok(day(20_765)),
+                Ok(Value::simple(Number::integer(68), "days")),
+                Ok(Value::simple(Number::integer(555), "minutes")),
+            ]
+        );
+
+        assert_eq!(
+            parse_in(
+                ENVIRONMENT.clone(),
+                "today * 2\ntoday + 2 kg\n3pm - today\n5 - today\ntoday in hours"
+            ),
+            vec![
+                Err(DedoError::DateArithmetic("multiplied".into())),
+                Err(DedoError::NotADuration("kg".into())),
+                Err(DedoError::DateArithmetic("subtracted from times of day".into())),
+                Err(DedoError::DateArithmetic("subtracted from numbers".into())),
+                Err(DedoError::DateArithmetic("converted to hours".into())),
+            ]
+        );
+
+        let out_of_range = "moved outside the years 0 to 9999".to_string();
+        let out_of_range = Err(DedoError::DateArithmetic(out_of_range));
+        assert_eq!(
+            parse_in(
+                ENVIRONMENT.clone(),
+                "2026-10-18 + 100000000000000000000 months
+\
+                 2026-10-18 + 1000000000000000000000 days
+9999-12-31 + 1 day"
+            ),
+            vec![out_of_range.clone(), out_of_range.clone(), out_of_range]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn time_zones() {
+        let mut env = ENVIRONMENT.clone();
+        env.set_clock(1_792_314_900);
+        env.set_time_zone("Europe/London").unwrap();
+        let zoned = |calendar, seconds: i64, zone: &str| {
+            let value = Value::moment(calendar, Number::integer(seconds));
+            ENVIRONMENT.zoned(value, zone.into())
+        };
+        let time = |seconds, zone| zoned(Calendar::Time, seconds, zone);
+
+        // London is on summer time until October 25th, so 9:15 there is
+        // 0:15 in California
+        assert_eq!(
+            parse_in(env.clone(), "9am London in Tokyo\n23:30 Tokyo in London\nnow in PST"),
+            vec![
+                time(17 * 3600, "Tokyo"),
+                time(15 * 3600 + 1800, "London"),
+                zoned(Calendar::DateTime, 20_744 * 86_400 + 900, "PST"),
+            ]
+        );
+
+        env.set_clock(1_792_314_900 + 8 * 86_400);
+        assert_eq!(parse_in(env.clone(), "9am London in Tokyo"), vec![time(18 * 3600, "Tokyo")]);
+
+        let unknown = Err(DedoError::UnknownTimeZone("Atlantis".into()));
+        assert_eq!(env.set_time_zone("Atlantis"), unknown);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn imperial_units() {
+        let results = parse_in(
+            ENVIRONMENT.clone(),
+            "30 knots in km/h\n32 psi in kPa\n1 hp in W\n1 BTU in J\n60 mph in km/h",
+        );
+        let expected = [55.56, 220.632233381, 745.69987158227, 1055.05585262, 96.56064];
+        for (result, expected) in results.iter().zip(expected.iter()) {
+            let num = result.as_ref().unwrap().num();
+            assert!((num - expected).abs() < 1e-6, "{} is not {}", num, expected);
+        }
+
+        // a knot is exactly 1852/3600 m/s, even though no float is
+        assert_eq!(
+            parse_in(ENVIRONMENT.clone(), "30 knots in km/h\n3600 kn in m/s\n7 kn in nmi/h"),
+            vec![
+                Ok(Value::new(
+                    Number::integer(5556) / Number::integer(100),
+                    units!("km" to 1, "h" to -1)
+                )),
+                Ok(Value::new(Number::integer(1852), units!("m" to 1, "s" to -1))),
+                Ok(Value::new(Number::integer(7), units!("nmi" to 1, "h" to -1))),
+            ]
+        );
+
+        // unit names win over lower case currency codes, but not over codes
+        assert_eq!(
+            parse_in(ENVIRONMENT.clone(), "2 cup\n10 CUP\n3 Nautical Miles\n1 fl oz"),
+            vec![
+                Ok(Value::simple(Number::integer(2), "cups")),
+                Ok(Value::simple(Number::integer(10), "CUP")),
+                Ok(Value::simple(Number::integer(3), "Nautical Miles")),
+                Ok(Value::simple(Number::integer(1), "fl oz")),
+            ]
+        );
+        assert_eq!(ENVIRONMENT.units.id(&"feet".into()), Some("ft".into()));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn simplification() {
+        let env = ENVIRONMENT.clone();
+
+        // units cancel through their dimensions too
+        assert_eq!(
+            parse_in(env.clone(), "12 m/sec * 1 hour\n10 J / 2 N\n2 Hz * 3 s\n3 N * 2 m"),
+            vec![
+                Ok(Value::simple(Number::integer(43200), "m")),
+                Ok(Value::simple(Number::integer(5), "m")),
+                Ok(Value::unitless(Number::integer(6))),
+                Ok(Value::new(Number::integer(6), units!("N" to 1, "m" to 1))),
+            ]
+        );
+
+        let mut best = env.clone();
+        best.set_unit_selection(UnitSelection::Best);
+        let input = "2cm\n1m\n3m\nsum\n0.00002 km\n90 minutes\n1500 ms\n2 m / 1 km\n4 lb";
+        assert_eq!(
+            parse_in(best, input)[3..],
+            [
+                Ok(Value::simple(4.02, "m")),
+                Ok(Value::simple(Number::integer(2), "cm")),
+                Ok(Value::simple(1.5, "hours")),
+                Ok(Value::simple(1.5, "s")),
+                Ok(Value::unitless(0.002)),
+                Ok(Value::simple(Number::integer(4), "lb")),
+            ]
+        );
+        assert_eq!(parse_in(env, "2cm\n1m\n3m\nsum")[3], Ok(Value::simple(402.0, "cm")));
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn unit_identity() {
+        let units = &ENVIRONMENT.units;
+        let id = |unit: &str| units.id(&Unit::from(unit)).map(|unit| unit.0);
+        let factor = |unit: &str| units.get(&unit.into()).factor;
+
+        assert_ne!(Unit::from("USD"), Unit::from("usd"));
+        assert_eq!(units!("USD" to 1, "usd" to 1).0.len(), 2);
+
+        // names go through aliases and case, symbols only through aliases
+        assert_eq!(id("Seconds"), Some("second".into()));
+        assert_eq!(id("sec"), Some("second".into()));
+        assert_eq!(id("Hours"), Some("hour".into()));
+        assert_eq!(id("MIN"), None);
+        assert_eq!(id("S"), None);
+
+        assert_eq!(factor("mm"), 0.001);
+        assert_eq!(factor("Mm"), 1e6);
+        assert_eq!(factor("Pa"), 1.0);
+        assert_eq!(
+            units.get(&"pa".into()).dimensions,
+            Dimensions::from(Dimension::Opaque("pa".into()))
+        );
+        assert_eq!(factor("msec"), 0.001);
+        assert_eq!(factor("kiloBYTES"), 1000.0);
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn unit_names() {
+        let units = &ENVIRONMENT.units;
+        let agree = |unit: &str, plural: bool| units.agree(&Unit::from(unit), plural).0;
+
+        assert_eq!(agree("hours", false), "hour");
+        assert_eq!(agree("Hour", true), "hours");
+        assert_eq!(agree("kilometre", true), "kilometres");
+        assert_eq!(agree("meters", false), "meter");
+        assert_eq!(agree("km", true), "km");
+        assert_eq!(agree("hertz", false), "hertz");
+
+        assert_eq!(plural("sprint"), Some("sprints".into()));
+        assert_eq!(plural("batch"), Some("batches".into()));
+        assert_eq!(plural("story"), Some("stories".into()));
+        assert_eq!(plural("day"), Some("days".into()));
+        assert_eq!(plural("kWh"), None);
+        assert_eq!(
+            units.get(&"glarps".into()).dimensions,
+            units.get(&"glarp".into()).dimensions
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn currency_codes() {
+        let units = &ENVIRONMENT.units;
+        let code = |unit: &str| units.currency_code(&Unit::from(unit));
+
+        assert_eq!(code("JPY"), Some("JPY".into()));
+        assert_eq!(code("¥"), Some("JPY".into()));
+        assert_eq!(code("chf"), Some("CHF".into()));
+        assert_eq!(code("ALL"), Some("ALL".into()));
+        assert_eq!(code("all"), None);
+        assert_eq!(code("min"), None);
+        assert_eq!(code("glarps"), None);
+        assert_eq!(currencies::find("BHD").map(|bhd| bhd.minor_units), Some(3));
+
+        // the fallback rates don't cover the Albanian lek
+        assert_eq!(
+            parse_in(ENVIRONMENT.clone(), "2 DKK + 1 ALL\n10 ALL in EUR"),
+            vec![
+                Err(DedoError::MissingRate("ALL".into())),
+                Err(DedoError::MissingRate("ALL".into())),
+            ]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn unit_definitions() {
+        let mut env = get_env();
+
+        assert_eq!(
+            env.define_unit("lap", 4.0, Value::simple(1.0, "km")),
+            Ok(Value::simple(1.0, "km"))
+        );
+        assert_eq!(
+            env.convert(Value::simple(2.0, "lap"), Value::simple(1.0, "m")),
+            Ok(Value::simple(500.0, "m"))
+        );
+        assert_eq!(
+            env.define_unit("nothing", 0.0, Value::simple(1.0, "km")),
+            Err(DedoError::DivisionByZero)
+        );
+
+        // existing units stay as they are, whichever name they go by
+        let input = "1 m = 2 ft\n1 km = 2 ft\n1 feet = 1 m\n1 lap = 400 m\n1 lap = 1 km";
+        assert_eq!(
+            parse_in(ENVIRONMENT.clone(), input)
+                .into_iter()
+                .map(|result| result.map_err(|error| error.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                Err("`m` is already a unit".to_string()),
+                Err("`km` is already a unit".to_string()),
+                Err("`feet` is already a unit".to_string()),
+                Ok(Value::simple(Number::integer(400), "m")),
+                Err("`lap` is already a unit".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[wasm_bindgen_test]
+    fn division_by_zero() {
+        let env = get_env();
+
+        assert_eq!(
+            env.div(Value::simple(2.0, "m"), Value::simple(0.0, "s")),
+            Err(DedoError::DivisionByZero)
+        );
+        assert_eq!(
+            env.pow(Value::unitless(0.0), Value::unitless(-1.0)),
+            Err(DedoError::DivisionByZero)
         );
     }
 }
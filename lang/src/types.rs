@@ -1,57 +1,181 @@
 use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
 use std::string::String;
 use serde::{Serialize, Deserialize};
+use crate::errors::DedoError;
+use crate::ast::Term;
+use crate::bigint::BigInt;
+use crate::currencies;
+use crate::dates::{self, Calendar};
+use crate::format;
+use crate::zones::{self, Zone};
+use crate::functions::{builtins, Builtin, UserFunction};
+use crate::number::{Arithmetic, Number, NumberFormat, Rational};
+use crate::rates::{RateProvider, Rates};
+use crate::units::{self, Dimension, Dimensions, Prefixes, UnitDef, UnitTable};
+use std::sync::Arc;
+
+/// Which units results are expressed in
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSelection {
+    /// The units the document wrote, e.g. `402 cm`
+    AsWritten,
+    /// The unit of its scale a single unit reads best in, e.g. `4.02 m`
+    Best,
+}
+
+/// How a value split across several units reads
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// `1 hour 30 minutes`
+    Words,
+    /// `01:30:00`
+    Clock,
+}
+
+/// A value split across several units, largest first, e.g. `5 ft 11 in`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mixed {
+    /// All positive, with the sign of the value kept apart
+    pub parts: Vec<Value>,
+    pub layout: Layout,
+    pub negative: bool,
+}
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Environment {
-    pub conversions: Conversions,
-    values: Vec<Result<Value, ()>>,
+    pub units: UnitTable,
+    values: Vec<Result<Value, DedoError>>,
     vars: HashMap<String, Value>,
+    functions: HashMap<String, Builtin>,
+    user_functions: HashMap<String, UserFunction>,
+    /// The document functions being evaluated, innermost last
+    calls: Vec<String>,
+    arithmetic: Arithmetic,
+    number_format: NumberFormat,
+    unit_selection: UnitSelection,
+    /// The wall clock time `now` and `today` stand for, if not the system's
+    clock: Option<i64>,
+    /// The zone of dates and times that don't name one, if not the system's
+    time_zone: Option<Zone>,
 }
 
 impl Environment {
-    pub fn new(conversions: &Conversions) -> Environment {
+    pub fn new(units: UnitTable) -> Environment {
         Environment {
-            conversions: Environment::expand_conversions(conversions),
+            units,
             values: Vec::new(),
             vars: HashMap::new(),
+            functions: builtins(),
+            user_functions: HashMap::new(),
+            calls: Vec::new(),
+            arithmetic: Arithmetic::Exact,
+            number_format: NumberFormat::Auto,
+            unit_selection: UnitSelection::AsWritten,
+            clock: None,
+            time_zone: None,
         }
     }
 
-    pub fn add_entry(&mut self, val: Result<Value, ()>) {
-        self.values.push(val);
+    /// Switches between float and exact arithmetic for the numbers
+    /// written from now on
+    pub fn set_arithmetic(&mut self, arithmetic: Arithmetic) {
+        self.arithmetic = arithmetic;
     }
 
-    fn conversion_ratio(&self, from: &Unit, to: &Unit) -> Result<f64, String> {
-        self.conversions
-            .0
-            .get(&(from.clone(), to.clone()))
-            .map(|ratio| ratio.clone())
-            .ok_or(format!("Cannot convert from {:?} to {:?}", from, to))
+    /// Picks the decimal separator of the numbers written from now on
+    pub fn set_number_format(&mut self, number_format: NumberFormat) {
+        self.number_format = number_format;
+    }
+
+    pub fn number_format(&self) -> NumberFormat {
+        self.number_format
+    }
+
+    /// Picks whether results keep the units they were written in or move
+    /// to the best unit of their scale
+    pub fn set_unit_selection(&mut self, unit_selection: UnitSelection) {
+        self.unit_selection = unit_selection;
+    }
+
+    /// Fixes the wall clock time, in seconds since 1970-01-01 00:00, that
+    /// `now` and `today` stand for
+    pub fn set_clock(&mut self, seconds: i64) {
+        self.clock = Some(seconds);
+    }
+
+    /// Sets the local time zone by a name like `Europe/London` or `PST`
+    pub fn set_time_zone(&mut self, name: &str) -> Result<(), DedoError> {
+        let zone = zones::find(name).ok_or_else(|| DedoError::UnknownTimeZone(name.into()))?;
+        self.time_zone = Some(zone);
+        Ok(())
     }
 
-    /// unit conversions e.g. 1km/h to 0.28m/s etc.
+    fn local_zone(&self) -> Zone {
+        match &self.time_zone {
+            Some(zone) => zone.clone(),
+            None => Zone::host(),
+        }
+    }
+
+    fn now(&self) -> i64 {
+        self.clock.unwrap_or_else(|| self.local_zone().to_local(dates::now()))
+    }
+
+    /// The date `offset` days from today, e.g. `-1` for yesterday
+    fn today(&self, offset: i64) -> Value {
+        let days = self.now().div_euclid(dates::SECONDS_PER_DAY) + offset;
+        Value::moment(Calendar::Date, Number::integer(days * dates::SECONDS_PER_DAY))
+    }
+
+    /// The closest `weekday` after today, or before it if `direction` is
+    /// negative, so on a Friday `next friday` is a week away
+    pub fn weekday(&self, direction: i64, weekday: u32) -> Value {
+        let today = self.now().div_euclid(dates::SECONDS_PER_DAY);
+        let ahead = (weekday as i64 - dates::weekday(today) as i64).rem_euclid(7);
+        let offset = match (direction < 0, ahead) {
+            (false, 0) => 7,
+            (false, ahead) => ahead,
+            (true, 0) => -7,
+            (true, ahead) => ahead - 7,
+        };
+        self.today(offset)
+    }
+
+    /// A number as written in the document
+    pub fn number(&self, num: Rational) -> Number {
+        Number::literal(num, self.arithmetic)
+    }
+
+    pub fn add_entry(&mut self, val: Result<Value, DedoError>) {
+        self.values.push(val);
+    }
+
+    /// unit conversions e.g. 1km/h to 0.28m/s etc. Every unit of `value`
+    /// is replaced by the unit of `new_units` measuring the same
+    /// dimensions, if there is one
     pub fn convert_units(&self, value: &Value, new_units: &UnitSet) -> Value {
         let mut converted = value.clone();
 
-        match value {
-            Value {
-                num: _,
-                units: UnitSet(units),
-            } => {
-                for (unit, val) in units.iter() {
-                    for (to_unit, _) in new_units.0.iter() {
-                        match self.conversion_ratio(unit, to_unit) {
-                            Err(_) => {
-                                // pass
-                            }
-                            Ok(ratio) => {
-                                converted.num *= ratio.powf(*val as f64);
-                                converted.units.0.remove(&unit);
-                                converted.units.0.insert(to_unit.clone(), val.clone());
-                            }
-                        }
-                    }
+        for (unit, pow) in value.units.0.iter() {
+            let from = self.units.get(unit);
+            let target = new_units
+                .0
+                .keys()
+                .find(|to_unit| self.units.get(to_unit).dimensions == from.dimensions);
+
+            match target {
+                Some(to_unit) if to_unit != unit => {
+                    let ratio = from.ratio_to(&self.units.get(to_unit));
+                    converted.num = converted.num * ratio.pow(*pow);
+                    converted.units.0.remove(unit);
+                    *converted.units.0.entry(to_unit.clone()).or_insert(0) += pow;
+                }
+                _ => {
+                    // nothing to convert to
                 }
             }
         }
@@ -59,72 +183,116 @@ impl Environment {
         converted
     }
 
-    fn expand_conversions(basic_conversions: &Conversions) -> Conversions {
-        let mut conversions = HashMap::new();
+    /// Expresses `value` in exactly the `target` units, as long as both
+    /// measure the same dimensions
+    fn convert_exact(&self, value: &Value, target: &UnitSet) -> Option<Value> {
+        let converted = self.convert_units(value, target);
+        if converted.units == *target {
+            return Some(converted);
+        }
 
-        // Add a -> b, b -> a, a -> a and b -> b conversions
-        for conversion in basic_conversions.clone().0 {
-            match conversion {
-                ((from, to), ratio) => {
-                    conversions.insert((from.clone(), to.clone()), ratio.clone());
-                    conversions.insert((to.clone(), from.clone()), 1. / ratio);
-                    conversions.insert((from.clone(), from.clone()), 1.);
-                    conversions.insert((to.clone(), to.clone()), 1.);
-                }
-            }
+        // the units don't pair up one by one, e.g. `m^3` to `m*cm^2`
+        let from = self.units.resolve(&value.units);
+        let to = self.units.resolve(target);
+        if from.dimensions != to.dimensions {
+            return None;
         }
 
-        // for all a -> b and b -> c, add a -> c, aka transitive conversions
-
-        loop {
-            // loop until no changes are made anymore
-            let mut is_saturated = true;
-
-            let mut new_conversions = HashMap::new();
-            for ((left_from, left_to), left_ratio) in &conversions {
-                for ((right_from, right_to), right_ratio) in &conversions {
-                    let key = (left_from.clone(), right_to.clone());
-                    if left_to == right_from
-                        && !conversions.contains_key(&key)
-                    {
-                        new_conversions.insert(
-                            key,
-                            left_ratio * right_ratio,
-                        );
-                        is_saturated = false;
-                    }
-                }
-            }
+        Some(Value::new(value.number() * from.ratio_to(&to), target.clone()))
+    }
 
-            if is_saturated {
-                break;
-            }
+    /// The definition of `units` if it is a single unit, e.g. `°C` but not
+    /// `°C^2` or `°C/s`
+    fn single_unit(&self, units: &UnitSet) -> Option<UnitDef> {
+        match units.0.iter().collect::<Vec<_>>().as_slice() {
+            [(unit, 1)] => Some(self.units.get(unit)),
+            _ => None,
+        }
+    }
 
-            conversions.extend(new_conversions);
+    /// `30 °C - 20 °C` is the distance between two points on an affine
+    /// scale, so it is expressed in the canonical unit: `10 K`
+    fn affine_difference(&self, left: &Value, right: &Value) -> Option<Value> {
+        let left_def = self.single_unit(&left.units)?;
+        let right_def = self.single_unit(&right.units)?;
+        let both_affine = left_def.is_affine() && right_def.is_affine();
+        if !both_affine || left_def.dimensions != right_def.dimensions {
+            return None;
         }
 
-        Conversions(conversions)
+        let canonical = self.units.canonical(&left_def.dimensions)?;
+        let num = left.num() * left_def.factor - right.num() * right_def.factor
+            + (left_def.offset - right_def.offset);
+        Some(Value::new(num, canonical))
     }
 
-    pub fn add(&self, left: Value, right: Value) -> Value {
-        let converted_right = self.convert_units(&right, &left.units);
+    /// Brings both operands of an addition or subtraction to the units of
+    /// the left one. A unitless operand takes the units of the other one,
+    /// so `$5 + 3` is `$8`.
+    fn align_units(&self, left: Value, right: Value) -> Result<(Value, Value), DedoError> {
+        if left.units.0.is_empty() {
+            let units = right.units.clone();
+            return Ok((Value::new(left.num, units), right));
+        }
 
-        Value {
-            num: left.num + converted_right.num,
-            units: left.units,
+        if right.units.0.is_empty() {
+            let units = left.units.clone();
+            return Ok((left, Value::new(right.num, units)));
+        }
+
+        match self.convert_exact(&right, &left.units) {
+            Some(converted_right) => Ok((left, converted_right)),
+            None => Err(self.incompatible(&right.units, &left.units)),
         }
     }
 
-    pub fn sub(&self, left: Value, right: Value) -> Value {
-        let converted_right = self.convert_units(&right, &left.units);
+    /// Why `from` can't be converted to `to`: a currency the rate provider
+    /// has no rate for, or else units that measure different things
+    fn incompatible(&self, from: &UnitSet, to: &UnitSet) -> DedoError {
+        let mut units = from.0.keys().chain(to.0.keys());
+        match units.find_map(|unit| self.units.missing_rate(unit)) {
+            Some(code) => DedoError::MissingRate(code),
+            None => DedoError::IncompatibleUnits(from.to_string(), to.to_string()),
+        }
+    }
 
-        Value {
-            num: left.num - converted_right.num,
-            units: left.units,
+    pub fn add(&self, left: Value, right: Value) -> Result<Value, DedoError> {
+        match (left.calendar, right.calendar) {
+            (Some(_), Some(_)) => return Err(DedoError::DateArithmetic("added together".into())),
+            (Some(_), None) => return self.shift(left, right),
+            (None, Some(_)) => return self.shift(right, left),
+            (None, None) => {}
         }
+
+        let (left, right) = self.align_units(left, right)?;
+
+        Ok(self.simplify(Value::new(left.num + right.num, left.units)))
     }
 
-    pub fn mul(&self, left: Value, right: Value) -> Value {
+    pub fn sub(&self, left: Value, right: Value) -> Result<Value, DedoError> {
+        match (left.calendar, right.calendar) {
+            (Some(_), Some(_)) => return self.between(left, right),
+            (Some(_), None) => return self.shift(left, right.negate()),
+            (None, Some(_)) => {
+                return Err(DedoError::DateArithmetic("subtracted from numbers".into()))
+            }
+            (None, None) => {}
+        }
+
+        if let Some(difference) = self.affine_difference(&left, &right) {
+            return Ok(difference);
+        }
+
+        let (left, right) = self.align_units(left, right)?;
+
+        Ok(self.simplify(Value::new(left.num - right.num, left.units)))
+    }
+
+    pub fn mul(&self, left: Value, right: Value) -> Result<Value, DedoError> {
+        if left.calendar.is_some() || right.calendar.is_some() {
+            return Err(DedoError::DateArithmetic("multiplied".into()));
+        }
+
         let converted_right = self.convert_units(&right, &left.units);
         let mut result_units = left.units.0.clone();
 
@@ -137,13 +305,18 @@ impl Environment {
             }
         }
 
-        Value {
-            num: left.num * converted_right.num,
-            units: UnitSet(result_units),
-        }
+        Ok(self.simplify(Value::new(left.num * converted_right.num, UnitSet(result_units))))
     }
 
-    pub fn div(&self, left: Value, right: Value) -> Value {
+    pub fn div(&self, left: Value, right: Value) -> Result<Value, DedoError> {
+        if left.calendar.is_some() || right.calendar.is_some() {
+            return Err(DedoError::DateArithmetic("divided".into()));
+        }
+
+        if right.num.is_zero() {
+            return Err(DedoError::DivisionByZero);
+        }
+
         let inverted_units = right
             .units
             .0
@@ -152,15 +325,14 @@ impl Environment {
             .map(|(k, v)| (k, -v))
             .collect();
 
-        let inverted_right = Value {
-            num: 1.0 / right.num,
-            units: UnitSet(inverted_units),
-        };
+        let inverted_right = Value::new(right.num.recip(), UnitSet(inverted_units));
 
         self.mul(left, inverted_right)
     }
 
-    /// Unit powers
+    /// Unit powers. Units can be raised to fractional powers as long as
+    /// they come out whole, so `(16 m^2) ^ 0.5` is `4 m` but `(2 m) ^ 0.5`
+    /// is an error
     ///
     /// Example:
     /// ```
@@ -170,102 +342,601 @@ impl Environment {
     /// # use dedo_rust::defaults::*;
     /// # let env = ENVIRONMENT.clone();
     /// let res = env.pow(Value::simple(12.0, "usd"), Value::unitless(4.0));
-    /// assert_eq!(res, Value::new(20736.0, units!("usd" to 4)));
+    /// assert_eq!(res, Ok(Value::new(20736.0, units!("usd" to 4))));
     /// ```
-    pub fn pow(&self, left: Value, right: Value) -> Value {
-        let pow = right.num.round();
+    pub fn pow(&self, left: Value, right: Value) -> Result<Value, DedoError> {
+        if left.calendar.is_some() || right.calendar.is_some() {
+            return Err(DedoError::DateArithmetic("raised to powers".into()));
+        }
 
-        let units = left
-            .units
-            .0
-            .into_iter()
-            .map(|(k, v)| (k, v * (pow as i32)))
+        let exponent = right.number();
+        if left.num.is_zero() && exponent.to_f64() < 0.0 {
+            return Err(DedoError::DivisionByZero);
+        }
+
+        let units = match exponent.to_rational().or_else(|| Rational::from_f64(right.num())) {
+            Some(exponent) => left.units.pow_exact(&exponent),
+            None => None,
+        };
+        let units = match units {
+            Some(units) => units,
+            None if left.is_unitless() => UnitSet(HashMap::new()),
+            None => return Err(DedoError::FractionalUnitPower(left.units.to_string())),
+        };
+
+        Ok(self.simplify(Value::new(left.num.raise(&exponent), units)))
+    }
+
+    /// Moves a date or time by a duration. Months and years are calendar
+    /// ones, so a month after January 31st is the last day of February,
+    /// and times of day wrap around midnight.
+    fn shift(&self, moment: Value, duration: Value) -> Result<Value, DedoError> {
+        let calendar = moment.calendar.unwrap_or(Calendar::DateTime);
+        let zone = moment.zone.clone();
+        let zoned = |calendar, seconds| Value {
+            zone: zone.clone(),
+            ..Value::moment(calendar, seconds)
+        };
+        let day = Number::integer(dates::SECONDS_PER_DAY);
+        let out_of_range = || {
+            let years = format!("moved outside the years 0 to {}", dates::MAX_YEAR);
+            DedoError::DateArithmetic(years)
+        };
+
+        let months = match duration.units.0.iter().collect::<Vec<_>>().as_slice() {
+            [(unit, 1)] => match self.units.id(unit) {
+                Some(Unit(name)) if name == "month" => Some(duration.number()),
+                Some(Unit(name)) if name == "year" => Some(duration.number() * Number::integer(12)),
+                _ => None,
+            },
+            _ => None,
+        };
+        let whole_months = months.filter(|months| months.clone().floor() == *months);
+        if let (Some(months), false) = (whole_months, calendar == Calendar::Time) {
+            let days = (moment.number() / day.clone()).floor();
+            let time_of_day = moment.number() - days.clone() * day.clone();
+            let days = dates::add_months(days.to_f64() as i64, months.to_f64() as i64)
+                .ok_or_else(out_of_range)?;
+            let seconds = Number::integer(days) * day + time_of_day;
+            return Ok(zoned(calendar, seconds));
+        }
+
+        let second = self.units.canonical(&Dimension::Time.into()).unwrap_or_else(|| "s".into());
+        let seconds = self
+            .convert_exact(&duration, &UnitSet::from(second))
+            .ok_or_else(|| DedoError::NotADuration(duration.units.to_string()))?;
+        let shifted = moment.num + seconds.num;
+        let days = (shifted.clone() / day.clone()).floor();
+        if calendar != Calendar::Time && dates::whole_seconds(shifted.to_f64()).is_none() {
+            return Err(out_of_range());
+        }
+
+        Ok(match calendar {
+            Calendar::Time => zoned(calendar, shifted - days * day),
+            Calendar::Date if days.clone() * day.clone() != shifted => {
+                zoned(Calendar::DateTime, shifted)
+            }
+            _ => zoned(calendar, shifted),
+        })
+    }
+
+    /// How far apart two dates or times are, in the biggest of days,
+    /// hours and minutes that measures it whole, or else in seconds
+    fn between(&self, left: Value, right: Value) -> Result<Value, DedoError> {
+        let times = (left.calendar == Some(Calendar::Time), right.calendar == Some(Calendar::Time));
+        if times.0 != times.1 {
+            return Err(DedoError::DateArithmetic("subtracted from times of day".into()));
+        }
+
+        let right = match left.zone == right.zone {
+            true => right,
+            false => self.rezone(right, &self.zone_of(&left)),
+        };
+        let seconds = left.num - right.num;
+        let units = [("day", dates::SECONDS_PER_DAY), ("hour", 3600), ("minute", 60)];
+        let whole = units.iter().find_map(|(unit, size)| {
+            let count = seconds.clone() / Number::integer(*size);
+            let defined = self.units.id(&Unit::from(*unit)).is_some();
+            (defined && count.clone().floor() == count).then(|| Value::simple(count, *unit))
+        });
+
+        let second = self.units.canonical(&Dimension::Time.into()).unwrap_or_else(|| "s".into());
+        Ok(whole.unwrap_or_else(|| Value::new(seconds, second)))
+    }
+
+    /// The zone of a date or time, the local one unless it names another
+    fn zone_of(&self, value: &Value) -> Zone {
+        let zone = value.zone.as_deref().and_then(zones::find);
+        zone.unwrap_or_else(|| self.local_zone())
+    }
+
+    /// The same instant on the clocks of another zone. Times of day are
+    /// taken to be today's, for daylight saving.
+    fn rezone(&self, value: Value, zone: &Zone) -> Value {
+        let day = Number::integer(dates::SECONDS_PER_DAY);
+        let calendar = value.calendar.unwrap_or(Calendar::DateTime);
+        let today = self.now().div_euclid(dates::SECONDS_PER_DAY) * dates::SECONDS_PER_DAY;
+        let local = value.number().floor().to_f64() as i64;
+        let local = match calendar {
+            Calendar::Time => today + local,
+            _ => local,
+        };
+
+        let shift = zone.to_local(self.zone_of(&value).to_utc(local)) - local;
+        let shifted = value.num + Number::integer(shift);
+        match calendar {
+            Calendar::Time => {
+                let days = (shifted.clone() / day.clone()).floor();
+                Value::moment(calendar, shifted - days * day)
+            }
+            _ => Value::moment(Calendar::DateTime, shifted),
+        }
+    }
+
+    /// Attaches a time zone to a date or time, e.g. `9am London`
+    pub fn zoned(&self, value: Value, zone: String) -> Result<Value, DedoError> {
+        if value.calendar.is_none() || zones::find(&zone).is_none() {
+            return Err(DedoError::UnknownTimeZone(zone));
+        }
+
+        Ok(Value {
+            zone: Some(zone),
+            ..value
+        })
+    }
+
+    /// Converts a date or time to a time zone, e.g. `9am London in Tokyo`.
+    /// Anything else is converted to the unit of that name as usual.
+    pub fn convert_zone(&self, value: Value, zone: String) -> Result<Value, DedoError> {
+        if value.calendar.is_none() {
+            let target = self.ident(zone)?;
+            return self.convert(value, target);
+        }
+
+        let target = zones::find(&zone).ok_or_else(|| DedoError::UnknownTimeZone(zone.clone()))?;
+        Ok(Value {
+            zone: Some(zone),
+            ..self.rezone(value, &target)
+        })
+    }
+
+    /// Tidies the units of a result. Units of the same dimensions cancel,
+    /// so `m * hour / sec` is plain `m`, and units that only cancel through
+    /// their dimensions are expressed in a canonical unit, so `J / N` is
+    /// `m` and `J / (N * m)` has none. Then with `UnitSelection::Best` a
+    /// single unit moves along its scale, so `0.00002 km` is `2 cm`.
+    fn simplify(&self, value: Value) -> Value {
+        let value = self.cancel_units(value);
+        match self.unit_selection {
+            UnitSelection::AsWritten => value,
+            UnitSelection::Best => self.best_unit(value),
+        }
+    }
+
+    fn cancel_units(&self, value: Value) -> Value {
+        // the units measuring each dimensions, numerator ones first
+        let mut units: Vec<(&Unit, &i32)> = value.units.0.iter().collect();
+        units.sort_by(|(left, left_pow), (right, right_pow)| {
+            right_pow.cmp(left_pow).then_with(|| left.0.cmp(&right.0))
+        });
+        let mut targets: Vec<(Unit, UnitDef)> = Vec::new();
+        for (unit, _) in units {
+            let def = self.units.get(unit);
+            if !targets.iter().any(|(_, target)| target.dimensions == def.dimensions) {
+                targets.push((unit.clone(), def));
+            }
+        }
+
+        let targets = UnitSet(targets.into_iter().map(|(unit, _)| (unit, 1)).collect());
+        let mut converted = self.convert_units(&value, &targets);
+        converted.units.0.retain(|_, pow| *pow != 0);
+        if converted.units.0.len() < 2 {
+            return converted;
+        }
+
+        let resolved = self.units.resolve(&converted.units);
+        if resolved.is_affine() {
+            return converted;
+        }
+
+        let (dimension, pow) = match resolved.dimensions.0.iter().collect::<Vec<_>>().as_slice() {
+            [] => return Value::unitless(converted.num * resolved.size()),
+            [(dimension, pow)] => ((*dimension).clone(), **pow),
+            _ => return converted,
+        };
+        match self.units.canonical(&Dimensions::from(dimension)) {
+            Some(canonical) => {
+                let units = UnitSet::from(canonical).pow(pow);
+                self.convert_exact(&converted, &units).unwrap_or(converted)
+            }
+            None => converted,
+        }
+    }
+
+    /// The unit of the scale of `value`'s unit with the biggest amount of
+    /// at least one, or the smallest unit if there is none
+    fn best_unit(&self, value: Value) -> Value {
+        let unit = match value.units.0.iter().collect::<Vec<_>>().as_slice() {
+            [(unit, 1)] => (*unit).clone(),
+            _ => return value,
+        };
+        let scale = match self.units.scale(&unit) {
+            Some(scale) if !value.num.is_zero() => scale,
+            _ => return value,
+        };
+
+        let from = self.units.get(&unit);
+        let candidates: Vec<Value> = scale
+            .iter()
+            .map(|to| Value::new(value.number() * from.ratio_to(&self.units.get(to)), to.clone()))
             .collect();
+        let best = candidates
+            .iter()
+            .rev()
+            .find(|candidate| candidate.num().abs() >= 1.0)
+            .unwrap_or(&candidates[0]);
+
         Value {
-            num: left.num.powf(pow),
-            units: UnitSet(units),
+            num: best.num.clone(),
+            units: best.units.clone(),
+            ..value
         }
     }
 
-    pub fn ident(&self, ident: String) -> Result<Value, ()> {
+    pub fn ident(&self, ident: String) -> Result<Value, DedoError> {
         match (ident.as_ref(), self.vars.get(&ident)) {
             ("sum", _) => self.sum(),
             ("prod", _) => self.prod(),
             ("prev", _) => self.prev(),
             (_, Some(v)) => Ok(v.clone()),
-            (units, _) => Ok(Value::simple(1.0, units)),
+            ("now", _) => Ok(Value::moment(Calendar::DateTime, Number::integer(self.now()))),
+            ("today", _) => Ok(self.today(0)),
+            ("tomorrow", _) => Ok(self.today(1)),
+            ("yesterday", _) => Ok(self.today(-1)),
+            (units, _) => Ok(Value::simple(Number::integer(1), units)),
         }
     }
 
-    pub fn sum(&self) -> Result<Value, ()> {
-        let mut res: Result<Value, ()> = Err(());
-        for row in self.values.iter().rev() {
-            match (row.clone(), res.clone()) {
-                (Ok(lhs), Ok(rhs)) => {
-                    res = Ok(self.add(lhs, rhs));
-                }
-                (Ok(lhs), Err(())) => {
-                    res = Ok(lhs.clone());
-                }
-                _ => break,
+    /// Sizes every currency with the rates of `provider`
+    pub fn set_rate_provider(&mut self, provider: Arc<dyn RateProvider>) {
+        self.units.set_rates(Rates(provider));
+    }
+
+    /// Marks a currency amount with when its exchange rates were published,
+    /// so it is clear how fresh a conversion is
+    pub fn stamp_rates(&self, value: Value) -> Value {
+        let dimensions = self.units.resolve(&value.units).dimensions;
+        match self.units.rates_timestamp() {
+            Some(timestamp) if dimensions.0.contains_key(&Dimension::Currency) => {
+                value.with_rates_as_of(timestamp)
             }
+            _ => value,
         }
-        res
     }
 
-    pub fn prod(&self) -> Result<Value, ()> {
-        let mut res: Result<Value, ()> = Err(());
-        for row in self.values.iter().rev() {
-            match (row.clone(), res.clone()) {
-                (Ok(lhs), Ok(rhs)) => {
-                    res = Ok(self.mul(lhs, rhs));
-                }
-                (Ok(lhs), Err(())) => {
-                    res = Ok(lhs.clone());
-                }
-                _ => break,
-            }
+    /// Rounds an amount of a single currency to its minor units, e.g. cents
+    /// for `$` and whole yen for `¥`
+    pub fn round_to_minor_units(&self, value: Value) -> Value {
+        let minor_units = match value.units.0.iter().collect::<Vec<_>>().as_slice() {
+            [(unit, 1)] => self
+                .units
+                .currency_code(unit)
+                .and_then(|code| currencies::find(&code))
+                .map(|currency| currency.minor_units),
+            _ => None,
+        };
+
+        match minor_units {
+            Some(digits) => Value {
+                num: value.num.round(digits),
+                ..value
+            },
+            None => value,
+        }
+    }
+
+    /// Spells the names of `value`'s units to agree with its amount, so
+    /// `1 hours` is `1 hour` and `2 metre/second` is `2 metres/second`;
+    /// units in the denominator are always singular
+    pub fn spell_units(&self, value: Value) -> Value {
+        let plural = value.num.clone().abs() != Number::integer(1);
+        let units = value
+            .units
+            .0
+            .iter()
+            .map(|(unit, pow)| (self.units.agree(unit, plural && *pow > 0), *pow))
+            .collect();
+
+        let mixed = value.mixed.map(|mixed| Mixed {
+            parts: mixed.parts.into_iter().map(|part| self.spell_units(part)).collect(),
+            ..mixed
+        });
+
+        Value {
+            units: UnitSet(units),
+            mixed,
+            ..value
+        }
+    }
+
+    /// Defines `amount` of a new unit as `value`, e.g. `1 sprint = 2 weeks`,
+    /// so that later lines can convert to and from it, and as `sprints`.
+    /// Units that already exist, even through a prefix or an alias, can't
+    /// be redefined.
+    pub fn define_unit<U: Into<Unit>>(
+        &mut self,
+        unit: U,
+        amount: f64,
+        value: Value,
+    ) -> Result<Value, DedoError> {
+        let unit = unit.into();
+        if self.units.knows(&unit) {
+            return Err(DedoError::UnitAlreadyDefined(unit.0));
+        }
+
+        if amount == 0.0 {
+            return Err(DedoError::DivisionByZero);
+        }
+
+        self.units.define_derived(unit.clone(), value.num() / amount, &value.units, Prefixes::None);
+        if let Some(plural) = units::plural(&unit.0) {
+            self.units.define_name(unit.clone(), &unit.0, &plural);
+        }
+        Ok(value)
+    }
+
+    /// Defines a function closing over the variables defined so far
+    pub fn define_function<U: Into<String>>(&mut self, name: U, params: Vec<String>, body: Term) {
+        let function = UserFunction {
+            params,
+            body,
+            captured: self.vars.clone(),
+        };
+        self.user_functions.insert(name.into(), function);
+    }
+
+    pub fn user_function(&self, name: &str) -> Option<UserFunction> {
+        self.user_functions.get(name).cloned()
+    }
+
+    /// Marks `name` as being evaluated, failing if it already is, since
+    /// without conditionals a function calling itself never stops
+    pub fn enter_call(&mut self, name: &str) -> Result<(), DedoError> {
+        if self.calls.iter().any(|call| call == name) {
+            return Err(DedoError::RecursiveFunction(name.into()));
+        }
+
+        self.calls.push(name.into());
+        Ok(())
+    }
+
+    pub fn exit_call(&mut self) {
+        self.calls.pop();
+    }
+
+    /// Swaps the variables in scope, returning the previous ones
+    pub fn replace_vars(&mut self, vars: HashMap<String, Value>) -> HashMap<String, Value> {
+        std::mem::replace(&mut self.vars, vars)
+    }
+
+    /// Calls a function from the registry, e.g. `sqrt`
+    pub fn call(&self, name: &str, args: Vec<Value>) -> Result<Value, DedoError> {
+        let function = self
+            .functions
+            .get(name)
+            .ok_or_else(|| DedoError::UnknownFunction(name.into()))?;
+
+        if !function.accepts(args.len()) {
+            return Err(DedoError::WrongArgumentCount {
+                function: name.into(),
+                min: function.min_args,
+                max: function.max_args,
+                got: args.len(),
+            });
         }
-        res
+
+        (function.apply)(self, args)
     }
 
-    pub fn prev(&self) -> Result<Value, ()> {
-        for row in self.values.iter().rev() {
-            return row.clone();
+    /// Folds the block of successful rows right above the current line,
+    /// starting from the closest one
+    fn aggregate<F>(&self, op: F, empty: DedoError) -> Result<Value, DedoError>
+    where
+        F: Fn(&Self, Value, Value) -> Result<Value, DedoError>,
+    {
+        let mut rows = self
+            .values
+            .iter()
+            .rev()
+            .map_while(|row| row.as_ref().ok())
+            .filter(|row| row.function.is_none());
+        let last = rows.next().ok_or(empty)?;
+
+        rows.try_fold(last.clone(), |acc, row| op(self, row.clone(), acc))
+    }
+
+    pub fn sum(&self) -> Result<Value, DedoError> {
+        self.aggregate(Self::add, DedoError::EmptySum)
+    }
+
+    pub fn prod(&self) -> Result<Value, DedoError> {
+        self.aggregate(Self::mul, DedoError::EmptyProd)
+    }
+
+    pub fn prev(&self) -> Result<Value, DedoError> {
+        let mut rows = self.values.iter().rev();
+        let row = rows.find(|row| !matches!(row, Ok(value) if value.function.is_some()));
+        match row {
+            Some(Ok(value)) => Ok(value.clone()),
+            _ => Err(DedoError::EmptyPrev),
         }
-        return Err(());
     }
 
-    pub fn assign<U: Into<String>>(&mut self, ident: U, value: Value) -> Result<Value, ()> {
+    pub fn assign<U: Into<String>>(&mut self, ident: U, value: Value) -> Result<Value, DedoError> {
         self.vars.insert(ident.into(), value.clone());
-        Ok(value.clone())
+        Ok(value)
+    }
+
+    /// Expresses `value` in the units of `target`. A target like `cm` also
+    /// stands for its powers, so `1 m^2 in cm` is `10000 cm^2`. Values in
+    /// affine units are absolute, so `20 °C in °F` is `68 °F`.
+    pub fn convert(&self, value: Value, target: Value) -> Result<Value, DedoError> {
+        if value.calendar.is_some() {
+            let target = format!("converted to {}", target.units);
+            return Err(DedoError::DateArithmetic(target));
+        }
+
+        if value.units.0.is_empty() {
+            return Ok(Value::new(value.num, target.units));
+        }
+
+        let single_units = (self.single_unit(&value.units), self.single_unit(&target.units));
+        if let (Some(from), Some(to)) = single_units {
+            if (from.is_affine() || to.is_affine()) && from.dimensions == to.dimensions {
+                let num = to.from_canonical(from.to_canonical(value.num()));
+                return Ok(Value::new(num, target.units));
+            }
+        }
+
+        let from = self.units.resolve(&value.units);
+        let to = self.units.resolve(&target.units);
+        let target_units = match to.dimensions.root_of(&from.dimensions) {
+            Some(pow) => target.units.pow(pow),
+            None => target.units.clone(),
+        };
+
+        self.convert_exact(&value, &target_units).ok_or_else(|| {
+            match target.units.0.keys().find(|unit| !self.units.knows(unit)) {
+                Some(unit) => DedoError::UnknownUnit(unit.0.clone()),
+                None => self.incompatible(&value.units, &target.units),
+            }
+        })
+    }
+
+    /// Sums amounts written side by side, like `5 ft 11 in`, in the unit of
+    /// the last one. They must all measure the same thing.
+    pub fn compound(&self, values: Vec<Value>) -> Result<Value, DedoError> {
+        let units = values[values.len() - 1].units.clone();
+        let target = Value::new(Number::integer(1), units.clone());
+        values.into_iter().try_fold(Value::new(Number::integer(0), units), |total, value| {
+            let value = self.convert(value, target.clone())?;
+            self.add(total, value)
+        })
     }
 
-    pub fn convert(&self, value: Value, target: Value) -> Value {
-        self.convert_units(&value, &target.units)
+    /// Splits a value across several units, largest first, e.g. `5400 s in
+    /// hours and minutes`. All but the last unit get whole amounts, and the
+    /// value itself is kept in the last one. Written out in words, parts
+    /// before the first one that isn't zero are left out.
+    pub fn convert_mixed(
+        &self,
+        value: Value,
+        units: Vec<String>,
+        layout: Layout,
+    ) -> Result<Value, DedoError> {
+        let negative = value.num() < 0.0;
+        let value = if negative { value.negate() } else { value };
+        let targets: Vec<Value> = units
+            .into_iter()
+            .map(|unit| Value::simple(Number::integer(1), unit))
+            .collect();
+
+        let mut remaining = self.convert(value.clone(), targets[0].clone())?;
+        let total = self.convert(value, targets[targets.len() - 1].clone())?;
+        for pair in targets.windows(2) {
+            let larger = self.units.resolve(&pair[0].units);
+            let smaller = self.units.resolve(&pair[1].units);
+            let names = || (pair[0].units.to_string(), pair[1].units.to_string());
+            if larger.dimensions != smaller.dimensions {
+                let (larger, smaller) = names();
+                return Err(DedoError::IncompatibleUnits(larger, smaller));
+            }
+            if larger.factor <= smaller.factor {
+                let (larger, smaller) = names();
+                return Err(DedoError::UnitsOutOfOrder(larger, smaller));
+            }
+        }
+
+        // split the amount as shown, so `3599.99999999999 s` is an hour
+        // rather than 59 minutes and 60 seconds
+        let shown = total.num.clone().round(format::MAX_DECIMALS as i32);
+        if shown != total.num {
+            let shown = Value::new(shown, total.units.clone());
+            remaining = self.convert(shown, targets[0].clone())?;
+        }
+
+        let mut parts = Vec::new();
+        for target in targets.iter().skip(1) {
+            let whole = remaining.num.clone().floor();
+            parts.push(Value::new(whole.clone(), remaining.units.clone()));
+            remaining.num = remaining.num - whole;
+            remaining = self.convert(remaining, target.clone())?;
+        }
+        parts.push(remaining);
+        if layout == Layout::Words {
+            let first = parts.iter().position(|part| !part.num.is_zero());
+            parts.drain(..first.unwrap_or(parts.len() - 1));
+        }
+
+        let total = if negative { total.negate() } else { total };
+        Ok(Value {
+            mixed: Some(Mixed {
+                negative: negative && parts.iter().any(|part| !part.num.is_zero()),
+                parts,
+                layout,
+            }),
+            ..total
+        })
     }
 }
 
-/// Utility to help create a static environment. Basic syntax is
-/// environment!["some_unit" to "some_other_unit" is 123.456, ...]
+/// Utility to help create a static environment. Every dimension lists its
+/// canonical unit and the size of each other unit in terms of it. Units
+/// followed by `[Si]` or `[SiAndBinary]` accept prefixes, and affine units
+/// give the canonical value of their zero with `offset`:
+/// environment![Length: "m" [Si] { "ft" is 0.3048, ... }, ...]
+/// environment![Temperature: "K" { "°C" offset 273.15 is 1 }]
 #[macro_export]
 macro_rules! environment {
-    ($($from:literal to $to:literal is $num:literal),*) => {
+    ($(
+        $dimension:ident : $canonical:literal $([$canonical_prefixes:ident])? {
+            $($unit:literal $([$prefixes:ident])? $(offset $offset:tt)? is $factor:expr),* $(,)?
+        }
+    ),* $(,)?) => {
         {
-            let mut tmp_map: std::collections::HashMap<(Unit, Unit), f64>
-                = std::collections::HashMap::new();
+            use $crate::units::{Dimension, UnitDef, UnitTable};
+            let mut table = UnitTable::default();
             $(
-                tmp_map.insert(($from.into(), $to.into()), $num as f64);
+                table.define_canonical(
+                    $canonical,
+                    Dimension::$dimension,
+                    $crate::prefixes!($($canonical_prefixes)?),
+                );
+                $(
+                    table.define(
+                        $unit,
+                        UnitDef::new(Dimension::$dimension, ($factor) as f64)
+                            .with_prefixes($crate::prefixes!($($prefixes)?))
+                            .with_offset(0.0 $(+ ($offset) as f64)?),
+                    );
+                )*
             )*
-            Environment::new(&Conversions(tmp_map))
+            $crate::types::Environment::new(table)
         }
     } ;
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct Conversions(pub HashMap<(Unit, Unit), f64>);
+#[doc(hidden)]
+#[macro_export]
+macro_rules! prefixes {
+    () => { $crate::units::Prefixes::None };
+    ($prefixes:ident) => { $crate::units::Prefixes::$prefixes };
+}
 
-#[derive(Clone, Debug, Hash, Serialize, Deserialize)]
+/// A unit as written, e.g. `km` or `hours`. Two units are the same only
+/// if they are spelled the same; the unit table knows which spellings
+/// stand for the same unit.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Unit(pub String);
 
 impl From<&str> for Unit {
@@ -274,21 +945,89 @@ impl From<&str> for Unit {
     }
 }
 
-impl PartialEq for Unit {
-    fn eq(&self, other: &Self) -> bool {
-        self.0.to_lowercase() == other.0.to_lowercase()
+impl From<String> for Unit {
+    fn from(name: String) -> Self {
+        Unit(name)
     }
 }
 
-impl Eq for Unit {}
-
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UnitSet(pub HashMap<Unit, i32>);
 
+/// Renders units as e.g. `km/hour` or `m^2/(kg*s)`, sorted by name so
+/// the output is stable
+impl fmt::Display for UnitSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut units: Vec<(&Unit, &i32)> = self.0.iter().filter(|(_, pow)| **pow != 0).collect();
+        units.sort_by(|(left, _), (right, _)| left.0.cmp(&right.0));
+
+        let render = |units: Vec<(&Unit, i32)>| -> String {
+            units
+                .iter()
+                .map(|(unit, pow)| match pow {
+                    1 => unit.0.clone(),
+                    pow => format!("{}^{}", unit.0, pow),
+                })
+                .collect::<Vec<String>>()
+                .join("*")
+        };
+
+        let numerator: Vec<(&Unit, i32)> = units
+            .iter()
+            .filter(|(_, pow)| **pow > 0)
+            .map(|(unit, pow)| (*unit, **pow))
+            .collect();
+        let denominator: Vec<(&Unit, i32)> = units
+            .iter()
+            .filter(|(_, pow)| **pow < 0)
+            .map(|(unit, pow)| (*unit, -**pow))
+            .collect();
+
+        match (numerator.len(), denominator.len()) {
+            (_, 0) => write!(f, "{}", render(numerator)),
+            (0, _) => write!(f, "1/{}", render(denominator)),
+            (_, 1) => write!(f, "{}/{}", render(numerator), render(denominator)),
+            _ => write!(f, "{}/({})", render(numerator), render(denominator)),
+        }
+    }
+}
+
+impl UnitSet {
+    /// Raises every unit to the given power, e.g. `km/h` to `km^2/h^2`
+    pub fn pow(&self, pow: i32) -> UnitSet {
+        UnitSet(
+            self.0
+                .iter()
+                .filter(|_| pow != 0)
+                .map(|(unit, unit_pow)| (unit.clone(), unit_pow * pow))
+                .collect(),
+        )
+    }
+
+    /// Raises every unit to a fractional power, if every power comes out
+    /// whole, e.g. `m^2` to the `1/2` is `m` but `m` to the `1/3` is `None`
+    pub fn pow_exact(&self, pow: &Rational) -> Option<UnitSet> {
+        let mut units = HashMap::new();
+        for (unit, unit_pow) in self.0.iter() {
+            let raised = &Rational::from(BigInt::from(*unit_pow as i64)) * pow;
+            if !raised.is_integer() {
+                return None;
+            }
+
+            let raised = i32::try_from(raised.numerator().to_i64()?).ok()?;
+            if raised != 0 {
+                units.insert(unit.clone(), raised);
+            }
+        }
+
+        Some(UnitSet(units))
+    }
+}
+
 impl From<Unit> for UnitSet {
     fn from(unit: Unit) -> UnitSet {
         let mut unit_map: HashMap<Unit, i32> = HashMap::new();
-        unit_map.insert(unit.into(), 1);
+        unit_map.insert(unit, 1);
         UnitSet(unit_map)
     }
 }
@@ -309,26 +1048,118 @@ macro_rules! units {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Value {
-    num: f64,
+    num: Number,
     units: UnitSet,
+    /// When the exchange rates used for a currency amount were published
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rates_as_of: Option<String>,
+    /// Set for dates and times, whose `num` is then a number of seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    calendar: Option<Calendar>,
+    /// The time zone a date or time is in, as written, if not the local one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    zone: Option<String>,
+    /// Set when the value reads as several units, e.g. `1 hour 30 minutes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mixed: Option<Mixed>,
+    /// Set for lines defining a function, e.g. `tax(x)`, which are not
+    /// amounts, so `sum` and `prev` look past them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function: Option<String>,
 }
 
 impl Value {
-    pub fn new<U: Into<UnitSet>>(num: f64, units: U) -> Value {
+    pub fn new<N: Into<Number>, U: Into<UnitSet>>(num: N, units: U) -> Value {
         Value {
-            num,
+            num: num.into(),
             units: units.into(),
+            rates_as_of: None,
+            calendar: None,
+            zone: None,
+            mixed: None,
+            function: None,
+        }
+    }
+
+    /// The result of defining a function, shown as its signature
+    pub fn function(name: &str, params: &[String]) -> Value {
+        Value {
+            function: Some(format!("{}({})", name, params.join(", "))),
+            ..Value::unitless(Number::integer(0))
         }
     }
 
-    pub fn simple<U: Into<String>>(num: f64, units: U) -> Value {
+    /// The signature of the function a line defines, e.g. `tax(x)`
+    pub fn signature(&self) -> Option<&str> {
+        self.function.as_deref()
+    }
+
+    /// A date or time, `seconds` after 1970-01-01 00:00 or after midnight
+    pub fn moment<N: Into<Number>>(calendar: Calendar, seconds: N) -> Value {
+        Value {
+            calendar: Some(calendar),
+            ..Value::new(seconds, UnitSet(HashMap::new()))
+        }
+    }
+
+    pub fn calendar(&self) -> Option<Calendar> {
+        self.calendar
+    }
+
+    pub fn zone(&self) -> Option<&str> {
+        self.zone.as_deref()
+    }
+
+    pub fn mixed(&self) -> Option<&Mixed> {
+        self.mixed.as_ref()
+    }
+
+    pub fn simple<N: Into<Number>, U: Into<String>>(num: N, units: U) -> Value {
         let unit_str: String = units.into();
+        Value::new(num, Unit(unit_str))
+    }
+
+    pub fn num(&self) -> f64 {
+        self.num.to_f64()
+    }
+
+    pub fn number(&self) -> Number {
+        self.num.clone()
+    }
+
+    pub fn units(&self) -> &UnitSet {
+        &self.units
+    }
+
+    pub fn rates_as_of(&self) -> Option<&str> {
+        self.rates_as_of.as_deref()
+    }
+
+    pub fn with_rates_as_of<S: Into<String>>(self, timestamp: S) -> Value {
         Value {
-            num,
-            units: UnitSet::from(Unit(unit_str)),
+            rates_as_of: Some(timestamp.into()),
+            ..self
         }
     }
 
+    /// A percentage, `Value::percent(15.0)` is 15%
+    pub fn percent<N: Into<Number>>(num: N) -> Value {
+        Value::simple(num, "%")
+    }
+
+    pub fn is_percent(&self) -> bool {
+        self.units == UnitSet::from(Unit::from("%"))
+    }
+
+    pub fn is_unitless(&self) -> bool {
+        self.units.0.is_empty() && self.calendar.is_none()
+    }
+
+    /// A percentage as a plain number, 15% is 0.15
+    pub fn fraction(&self) -> Value {
+        Value::unitless(self.num.clone() / Number::integer(100))
+    }
+
     /// A value with no units and the given number
     ///
     /// Example:
@@ -336,11 +1167,8 @@ impl Value {
     /// # use dedo_rust::types::*;
     /// let value = Value::unitless(14.1);
     /// ```
-    pub fn unitless(num: f64) -> Value {
-        Value {
-            num,
-            units: UnitSet(HashMap::new()),
-        }
+    pub fn unitless<N: Into<Number>>(num: N) -> Value {
+        Value::new(num, UnitSet(HashMap::new()))
     }
 
     /// The zero value
@@ -356,7 +1184,7 @@ impl Value {
         Self::unitless(0.0)
     }
 
-    /// Negates a value
+    /// Negates a value, which is then no longer split across units
     ///
     /// Example:
     /// ```
@@ -369,7 +1197,8 @@ impl Value {
     pub fn negate(self) -> Value {
         Value {
             num: -self.num,
-            units: self.units,
+            mixed: None,
+            ..self
         }
     }
 }
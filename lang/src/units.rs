@@ -0,0 +1,567 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+use crate::bigint::BigInt;
+use crate::number::{Number, Rational};
+use crate::rates::Rates;
+use crate::types::{Unit, UnitSet};
+
+/// The base quantities every unit is measured in
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Dimension {
+    Length,
+    Mass,
+    Time,
+    Currency,
+    Data,
+    Temperature,
+    Angle,
+    /// Units the environment knows nothing about, e.g. `glarps`, are a
+    /// dimension of their own
+    Opaque(String),
+}
+
+/// Powers of base dimensions, e.g. a speed is `Length^1 * Time^-1`
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Dimensions(pub BTreeMap<Dimension, i32>);
+
+impl From<Dimension> for Dimensions {
+    fn from(dimension: Dimension) -> Self {
+        let mut powers = BTreeMap::new();
+        powers.insert(dimension, 1);
+        Dimensions(powers)
+    }
+}
+
+impl Dimensions {
+    /// `self * other^pow`
+    pub fn combine(&self, other: &Dimensions, pow: i32) -> Dimensions {
+        let mut powers = self.0.clone();
+
+        for (dimension, other_pow) in other.0.iter() {
+            let power = powers.entry(dimension.clone()).or_insert(0);
+            *power += other_pow * pow;
+
+            if *power == 0 {
+                powers.remove(dimension);
+            }
+        }
+
+        Dimensions(powers)
+    }
+
+    pub fn pow(&self, pow: i32) -> Dimensions {
+        Dimensions::default().combine(self, pow)
+    }
+
+    /// The power `k` for which `self^k == other`, if there is one
+    pub fn root_of(&self, other: &Dimensions) -> Option<i32> {
+        let (dimension, pow) = self.0.iter().next()?;
+        let other_pow = other.0.get(dimension)?;
+
+        if other_pow % pow != 0 {
+            return None;
+        }
+
+        let k = other_pow / pow;
+        if self.pow(k) == *other {
+            Some(k)
+        } else {
+            None
+        }
+    }
+}
+
+/// SI prefixes as (symbol, name, factor)
+const SI_PREFIXES: [(&str, &str, f64); 22] = [
+    ("Y", "yotta", 1e24),
+    ("Z", "zetta", 1e21),
+    ("E", "exa", 1e18),
+    ("P", "peta", 1e15),
+    ("T", "tera", 1e12),
+    ("G", "giga", 1e9),
+    ("M", "mega", 1e6),
+    ("k", "kilo", 1e3),
+    ("h", "hecto", 1e2),
+    ("da", "deca", 1e1),
+    ("d", "deci", 1e-1),
+    ("c", "centi", 1e-2),
+    ("m", "milli", 1e-3),
+    ("µ", "micro", 1e-6),
+    ("μ", "micro", 1e-6),
+    ("u", "micro", 1e-6),
+    ("n", "nano", 1e-9),
+    ("p", "pico", 1e-12),
+    ("f", "femto", 1e-15),
+    ("a", "atto", 1e-18),
+    ("z", "zepto", 1e-21),
+    ("y", "yocto", 1e-24),
+];
+
+/// IEC binary prefixes as (symbol, name, factor)
+const BINARY_PREFIXES: [(&str, &str, f64); 8] = [
+    ("Ki", "kibi", 1024.0),
+    ("Mi", "mebi", 1048576.0),
+    ("Gi", "gibi", 1073741824.0),
+    ("Ti", "tebi", 1099511627776.0),
+    ("Pi", "pebi", 1125899906842624.0),
+    ("Ei", "exbi", 1152921504606846976.0),
+    ("Zi", "zebi", 1180591620717411303424.0),
+    ("Yi", "yobi", 1208925819614629174706176.0),
+];
+
+/// Whether `name` looks like an English word, or words joined like
+/// `story_point`, whose plural can be guessed
+fn is_word(name: &str) -> bool {
+    let mut chars = name.chars();
+    name.len() >= 3
+        && chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// The regular plural of a word, e.g. `sprints` or `batches`
+pub fn plural(name: &str) -> Option<String> {
+    if !is_word(name) {
+        return None;
+    }
+
+    let sibilant = ["s", "x", "z", "ch", "sh"].iter().any(|end| name.ends_with(end));
+    let vowel_y = ["ay", "ey", "oy", "uy"].iter().any(|end| name.ends_with(end));
+    let consonant_y = name.ends_with('y') && !vowel_y;
+    Some(if sibilant {
+        format!("{}es", name)
+    } else if consonant_y {
+        format!("{}ies", &name[..name.len() - 1])
+    } else {
+        format!("{}s", name)
+    })
+}
+
+/// The regular singular of a word, so unknown units like `glarps` and
+/// `glarp` measure the same thing. Words ending in `ss`, `us` or `is`, like
+/// `bus`, are taken to be singular already.
+fn singular(name: &str) -> String {
+    let is_singular = ["ss", "us", "is"].iter().any(|end| name.ends_with(end));
+    if !is_word(name) || !name.ends_with('s') || is_singular {
+        return name.into();
+    }
+
+    let sibilant = ["sses", "xes", "zes", "ches", "shes"].iter().any(|end| name.ends_with(end));
+    // `buses` and `bonuses`, but not `houses` or `causes`
+    let us_plural =
+        name.ends_with("uses") && !["ouses", "auses"].iter().any(|end| name.ends_with(end));
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if sibilant || us_plural {
+        name[..name.len() - 2].into()
+    } else {
+        name[..name.len() - 1].into()
+    }
+}
+
+/// Which prefixes a unit accepts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Prefixes {
+    None,
+    /// `km`, `ms`, `µg`, `kilometre`, ...
+    Si,
+    /// SI plus IEC binary prefixes, e.g. `MiB` or `gibibyte`
+    SiAndBinary,
+}
+
+impl Prefixes {
+    fn accepts(self, binary: bool) -> bool {
+        match self {
+            Prefixes::None => false,
+            Prefixes::Si => !binary,
+            Prefixes::SiAndBinary => true,
+        }
+    }
+}
+
+lazy_static! {
+    /// Every prefix spelling as (prefix, factor, is binary), longest first
+    /// so that e.g. `da` is tried before `d`
+    static ref PREFIXES: Vec<(&'static str, f64, bool)> = {
+        let si = SI_PREFIXES.iter().map(|prefix| (prefix, false));
+        let binary = BINARY_PREFIXES.iter().map(|prefix| (prefix, true));
+
+        let mut prefixes: Vec<(&'static str, f64, bool)> = si
+            .chain(binary)
+            .flat_map(|(&(symbol, name, factor), is_binary)| {
+                vec![(symbol, factor, is_binary), (name, factor, is_binary)]
+            })
+            .collect();
+        prefixes.sort_by_key(|(prefix, _, _)| std::cmp::Reverse(prefix.len()));
+        prefixes
+    };
+}
+
+/// What a unit measures and how big it is
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnitDef {
+    pub dimensions: Dimensions,
+    /// How many canonical units of the same dimensions one of this unit is,
+    /// e.g. `1000` for `km` when `m` is canonical
+    pub factor: f64,
+    /// The factor as a fraction, when it is exact, e.g. 463/900 for `kn`
+    /// which no float can hold
+    pub exact: Option<Rational>,
+    /// Where the zero of this unit sits on the canonical scale, e.g.
+    /// `273.15` for `°C` against `K`. Only absolute amounts are shifted by
+    /// it, differences are scaled by `factor` alone.
+    pub offset: f64,
+    pub prefixes: Prefixes,
+}
+
+impl UnitDef {
+    pub fn new<D: Into<Dimensions>>(dimensions: D, factor: f64) -> UnitDef {
+        UnitDef {
+            dimensions: dimensions.into(),
+            factor,
+            exact: Rational::from_f64(factor),
+            offset: 0.0,
+            prefixes: Prefixes::None,
+        }
+    }
+
+    /// A unit whose factor is the fraction `exact`
+    pub fn from_exact<D: Into<Dimensions>>(dimensions: D, exact: Rational) -> UnitDef {
+        UnitDef {
+            dimensions: dimensions.into(),
+            factor: exact.to_f64(),
+            exact: Some(exact),
+            offset: 0.0,
+            prefixes: Prefixes::None,
+        }
+    }
+
+    /// `count` of this unit, e.g. `1000 m` for `km`, exactly if it can be
+    fn times(&self, count: f64) -> UnitDef {
+        let exact = Rational::from_f64(count).zip(self.exact.as_ref());
+        match exact {
+            Some((count, exact)) => UnitDef::from_exact(self.dimensions.clone(), &count * exact),
+            None => UnitDef::new(self.dimensions.clone(), self.factor * count),
+        }
+    }
+
+    pub fn with_prefixes(self, prefixes: Prefixes) -> UnitDef {
+        UnitDef { prefixes, ..self }
+    }
+
+    pub fn with_offset(self, offset: f64) -> UnitDef {
+        UnitDef { offset, ..self }
+    }
+
+    pub fn is_affine(&self) -> bool {
+        self.offset != 0.0
+    }
+
+    /// An absolute amount of this unit, e.g. `20 °C`, on the canonical scale
+    pub fn to_canonical(&self, num: f64) -> f64 {
+        num * self.factor + self.offset
+    }
+
+    /// An absolute amount on the canonical scale in this unit
+    pub fn from_canonical(&self, num: f64) -> f64 {
+        (num - self.offset) / self.factor
+    }
+
+    /// How many units of `other` one of this unit is, exact if both
+    /// factors are, so `sec` to `hour` is exactly `1/3600`
+    pub fn ratio_to(&self, other: &UnitDef) -> Number {
+        self.size() / other.size()
+    }
+
+    /// How many canonical units one of this unit is, exact if it can be
+    pub fn size(&self) -> Number {
+        match &self.exact {
+            Some(exact) => Number::from(exact.clone()),
+            None => Number::Float(self.factor),
+        }
+    }
+}
+
+/// Every unit known to an environment, each defined directly against the
+/// canonical unit of its dimensions, so conversions are a lookup away.
+/// Names are case sensitive, so `mm` and `Mm` or `Pa` and `pa` are
+/// different units, unless a name is marked with `ignore_case`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnitTable {
+    units: HashMap<Unit, UnitDef>,
+    canonical: HashMap<Dimension, Unit>,
+    /// Other names of defined units, e.g. `sec` and `seconds` for `second`
+    aliases: HashMap<Unit, Unit>,
+    /// The unit each name that can be written in any case stands for, by
+    /// its lower case spelling
+    folded: HashMap<String, Unit>,
+    /// The singular and plural of every name, by its lower case spelling,
+    /// e.g. `metre` and `metres` for both of them
+    names: HashMap<String, (String, String)>,
+    /// The ISO 4217 code of every currency unit, e.g. `EUR` for `€`
+    currencies: HashMap<Unit, String>,
+    rates: Option<Rates>,
+    /// Units of the same dimensions a result can be moved between to read
+    /// better, each smallest first, e.g. `mm`, `cm`, `m`, `km`
+    scales: Vec<Vec<Unit>>,
+}
+
+impl UnitTable {
+    pub fn define<U: Into<Unit>>(&mut self, unit: U, def: UnitDef) {
+        self.units.insert(unit.into(), def);
+    }
+
+    /// Defines the unit every other unit of `dimension` is sized against
+    pub fn define_canonical<U: Into<Unit>>(
+        &mut self,
+        unit: U,
+        dimension: Dimension,
+        prefixes: Prefixes,
+    ) {
+        let unit = unit.into();
+        self.canonical.insert(dimension.clone(), unit.clone());
+        self.define(unit, UnitDef::new(dimension, 1.0).with_prefixes(prefixes));
+    }
+
+    /// Defines another name for `unit`, which is sized, prefixed and
+    /// converted exactly like it
+    pub fn define_alias<A: Into<Unit>, U: Into<Unit>>(&mut self, alias: A, unit: U) {
+        let unit = unit.into();
+        let unit = self.aliases.get(&unit).cloned().unwrap_or(unit);
+        self.aliases.insert(alias.into(), unit);
+    }
+
+    /// Lets a unit or alias be written in any case, e.g. `Hour` or `HOURS`.
+    /// Meant for names; symbols like `mm` stay case sensitive.
+    pub fn ignore_case<U: Into<Unit>>(&mut self, name: U) {
+        let name = name.into();
+        if let Some(unit) = self.id(&name) {
+            self.folded.insert(name.0.to_lowercase(), unit);
+        }
+    }
+
+    /// Defines the singular and plural name of `unit`, e.g. `metre` and
+    /// `metres` for `m`. Names can be written in any case, and results are
+    /// spelt to agree with their amount, so `1 hours` is `1 hour`.
+    pub fn define_name<U: Into<Unit>>(&mut self, unit: U, singular: &str, plural: &str) {
+        let unit = unit.into();
+        for name in [singular, plural].iter() {
+            if unit.0 != *name {
+                self.define_alias(*name, unit.clone());
+            }
+            self.ignore_case(*name);
+            self.names.insert(name.to_lowercase(), (singular.into(), plural.into()));
+        }
+    }
+
+    /// `unit` spelt to agree with an amount, e.g. `hours` for one is `hour`
+    /// and `kilometre` for two is `kilometres`. Symbols stay as they are,
+    /// and so do currencies, e.g. `CUP` is not a cup.
+    pub fn agree(&self, unit: &Unit, plural: bool) -> Unit {
+        if self.currencies.contains_key(unit) {
+            return unit.clone();
+        }
+
+        let prefixes = PREFIXES.iter().map(|(prefix, _, _)| *prefix);
+        let spelling = std::iter::once("").chain(prefixes).find_map(|prefix| {
+            let name = unit.0.strip_prefix(prefix)?;
+            Some((prefix, name, self.names.get(&name.to_lowercase())?))
+        });
+
+        match spelling {
+            Some((prefix, name, (singular, plural_name))) => {
+                let agreeing = if plural { plural_name } else { singular };
+                if name.to_lowercase() == agreeing.to_lowercase() {
+                    unit.clone()
+                } else {
+                    Unit(format!("{}{}", prefix, agreeing))
+                }
+            }
+            None => unit.clone(),
+        }
+    }
+
+    /// The defined unit a name stands for, through aliases and case, e.g.
+    /// `seconds` and `Second` are both `second` but `S` is nothing
+    pub fn id(&self, name: &Unit) -> Option<Unit> {
+        if self.units.contains_key(name) {
+            return Some(name.clone());
+        }
+
+        self.aliases
+            .get(name)
+            .or_else(|| self.folded.get(&name.0.to_lowercase()))
+            .cloned()
+    }
+
+    /// The canonical unit of a base dimension like `Length`
+    pub fn canonical(&self, dimensions: &Dimensions) -> Option<Unit> {
+        match dimensions.0.iter().collect::<Vec<_>>().as_slice() {
+            [(dimension, 1)] => self.canonical.get(dimension).cloned(),
+            _ => None,
+        }
+    }
+
+    /// Defines a currency unit by its ISO 4217 code. Unlike other units it
+    /// has no fixed size, it is sized by the rate provider on every lookup.
+    pub fn define_currency<U: Into<Unit>>(&mut self, unit: U, code: &str) {
+        self.currencies.insert(unit.into(), code.into());
+    }
+
+    /// Defines a scale of units, smallest first, so `90 minutes` can be
+    /// shown as `1.5 hours` when the environment picks the best unit
+    pub fn define_scale<U: Into<Unit>>(&mut self, units: Vec<U>) {
+        self.scales.push(units.into_iter().map(Into::into).collect());
+    }
+
+    /// The scale `unit` is part of, if any
+    pub fn scale(&self, unit: &Unit) -> Option<&[Unit]> {
+        self.scales.iter().find(|scale| scale.contains(unit)).map(Vec::as_slice)
+    }
+
+    pub fn set_rates(&mut self, rates: Rates) {
+        self.rates = Some(rates);
+    }
+
+    /// When the rates in use were published, if the provider knows
+    pub fn rates_timestamp(&self) -> Option<String> {
+        self.rates.as_ref()?.0.timestamp()
+    }
+
+    /// How many names the table knows, aliases and currencies included
+    pub fn len(&self) -> usize {
+        self.units.len() + self.aliases.len() + self.currencies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Defines `unit` as `factor` times the compound `units`, e.g. a
+    /// newton is `kg*m/s^2`
+    pub fn define_derived<U: Into<Unit>>(
+        &mut self,
+        unit: U,
+        factor: f64,
+        units: &UnitSet,
+        prefixes: Prefixes,
+    ) {
+        let def = self.resolve(units);
+        self.define(unit, def.times(factor).with_prefixes(prefixes));
+    }
+
+    /// Like `define_derived`, with a factor no float can hold, e.g. `kn`
+    /// as 1852/3600 m/s
+    pub fn define_ratio<U: Into<Unit>>(
+        &mut self,
+        unit: U,
+        (numerator, denominator): (i64, i64),
+        units: &UnitSet,
+        prefixes: Prefixes,
+    ) {
+        let def = self.resolve(units);
+        let ratio = Rational::new(BigInt::from(numerator), BigInt::from(denominator));
+        let exact = def.exact.map(|exact| &exact * &ratio);
+        let def = match exact {
+            Some(exact) => UnitDef::from_exact(def.dimensions, exact),
+            None => UnitDef::new(def.dimensions, def.factor * ratio.to_f64()),
+        };
+        self.define(unit, def.with_prefixes(prefixes));
+    }
+
+    /// Whether `unit` is defined, or a currency, rather than a word the
+    /// table has never heard of
+    pub fn knows(&self, unit: &Unit) -> bool {
+        self.lookup(&unit.0).is_some() || self.currency_code(unit).is_some()
+    }
+
+    /// The definition of a single unit; unknown units are opaque
+    pub fn get(&self, unit: &Unit) -> UnitDef {
+        if let Some(code) = self.currencies.get(unit) {
+            return self.currency(code);
+        }
+
+        if let Some(def) = self.lookup(&unit.0) {
+            return def;
+        }
+
+        match self.currency_code(unit) {
+            Some(code) => self.currency(&code),
+            None => UnitDef::new(Dimension::Opaque(singular(&unit.0)), 1.0),
+        }
+    }
+
+    /// The ISO 4217 code of a currency unit. Codes only the rate provider
+    /// knows are currencies too, as long as they are written in upper case
+    /// and aren't the name of another unit.
+    pub fn currency_code(&self, unit: &Unit) -> Option<String> {
+        if let Some(code) = self.currencies.get(unit) {
+            return Some(code.clone());
+        }
+
+        let is_code = unit.0.chars().all(|c| c.is_ascii_uppercase());
+        if !is_code || self.lookup(&unit.0).is_some() {
+            return None;
+        }
+
+        let rates = self.rates.as_ref()?;
+        rates.0.rate(&unit.0).map(|_| unit.0.clone())
+    }
+
+    /// The code of `unit` if it is a currency the rate provider has no
+    /// rate for, e.g. `ALL` with the fallback rates
+    pub fn missing_rate(&self, unit: &Unit) -> Option<String> {
+        let code = self.currency_code(unit)?;
+        let dimensions = self.get(unit).dimensions;
+        (!dimensions.0.contains_key(&Dimension::Currency)).then_some(code)
+    }
+
+    /// Currencies are sized against the provider's base currency. Without
+    /// a rate a currency can't be converted at all, so it is opaque.
+    fn currency(&self, code: &str) -> UnitDef {
+        let rates = self.rates.as_ref();
+        match rates.and_then(|rates| rates.0.exchange(code, rates.0.base())) {
+            Some(factor) => UnitDef::new(Dimension::Currency, factor),
+            None => UnitDef::new(Dimension::Opaque(code.into()), 1.0),
+        }
+    }
+
+    /// The definition of the unit `name` stands for
+    fn definition(&self, name: &str) -> Option<&UnitDef> {
+        self.units.get(&self.id(&Unit(name.into()))?)
+    }
+
+    /// Exact names always win over prefixed ones, so `min` stays a minute
+    /// and `m` a metre. Otherwise the longest prefix whose remainder is a
+    /// prefixable unit is used, e.g. `Mm` is a megametre and `mm` a
+    /// millimetre.
+    fn lookup(&self, name: &str) -> Option<UnitDef> {
+        if let Some(def) = self.definition(name) {
+            return Some(def.clone());
+        }
+
+        PREFIXES.iter().find_map(|(prefix, factor, is_binary)| {
+            let base = self.definition(name.strip_prefix(prefix)?)?;
+            if !base.prefixes.accepts(*is_binary) {
+                return None;
+            }
+
+            Some(base.times(*factor))
+        })
+    }
+
+    /// The dimensions and size of a compound unit like `km/hour`
+    pub fn resolve(&self, units: &UnitSet) -> UnitDef {
+        units
+            .0
+            .iter()
+            .fold(UnitDef::new(Dimensions::default(), 1.0), |acc, (unit, pow)| {
+                let def = self.get(unit);
+                let dimensions = acc.dimensions.combine(&def.dimensions, *pow);
+                let power = def.exact.and_then(|exact| exact.pow(*pow));
+                match acc.exact.zip(power) {
+                    Some((acc, power)) => UnitDef::from_exact(dimensions, &acc * &power),
+                    None => UnitDef::new(dimensions, acc.factor * def.factor.powf(*pow as f64)),
+                }
+            })
+    }
+}
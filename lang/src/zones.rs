@@ -0,0 +1,749 @@
+use crate::dates::{self, SECONDS_PER_DAY};
+
+/// A time zone, as an offset from UTC and the daylight saving rule of a
+/// region of the IANA database, if it has one
+#[derive(Clone, Debug, PartialEq)]
+pub struct Zone {
+    offset: i64,
+    dst: Option<Dst>,
+    /// Set for the host's own zone, whose offsets are looked up instead
+    host: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Dst {
+    offset: i64,
+    start: Transition,
+    end: Transition,
+}
+
+/// When clocks change, as a day of the year and a time of that day in
+/// the local time before the change
+#[derive(Clone, Debug, PartialEq)]
+struct Transition {
+    day: Day,
+    time: i64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Day {
+    /// `M3.5.0` is the last (5th) Sunday (0) of March (3)
+    Weekday { month: u32, week: u32, weekday: u32 },
+    /// `J60` is March 1st, even in leap years
+    Julian(u32),
+    /// `59` is February 29th in leap years, counting from 0
+    Ordinal(u32),
+}
+
+impl Zone {
+    /// A zone that is always `offset` seconds ahead of UTC
+    pub fn fixed(offset: i64) -> Zone {
+        Zone {
+            offset,
+            dst: None,
+            host: false,
+        }
+    }
+
+    /// The zone the host's clock is in, with the offset it has at each
+    /// time, see `dates::local_offset`
+    pub fn host() -> Zone {
+        Zone {
+            host: true,
+            ..Zone::fixed(0)
+        }
+    }
+
+    /// How far ahead of UTC the clocks of the zone are at a UTC time
+    pub fn offset(&self, utc: i64) -> i64 {
+        if self.host {
+            return dates::local_offset(utc);
+        }
+
+        let dst = match &self.dst {
+            Some(dst) => dst,
+            None => return self.offset,
+        };
+
+        let (year, _, _) = dates::civil_from_days((utc + self.offset).div_euclid(SECONDS_PER_DAY));
+        let start = dst.start.at(year) - self.offset;
+        let end = dst.end.at(year) - dst.offset;
+        // in the southern hemisphere daylight saving spans the new year
+        let summer = if start < end {
+            start <= utc && utc < end
+        } else {
+            utc < end || start <= utc
+        };
+
+        if summer {
+            dst.offset
+        } else {
+            self.offset
+        }
+    }
+
+    /// The wall clock time of the zone at a UTC time
+    pub fn to_local(&self, utc: i64) -> i64 {
+        utc + self.offset(utc)
+    }
+
+    /// The UTC time of a wall clock time of the zone. Times repeated when
+    /// clocks go back are the first of the two, and times skipped when
+    /// they go forward are read with the offset from before the change.
+    pub fn to_utc(&self, local: i64) -> i64 {
+        // the offsets the zone may have then, biggest first, and the one
+        // from before a change
+        let (offsets, before) = match (&self.dst, self.host) {
+            (_, true) => {
+                let before = dates::local_offset(local - SECONDS_PER_DAY);
+                let after = dates::local_offset(local + SECONDS_PER_DAY);
+                (vec![before.max(after), before.min(after)], before)
+            }
+            (Some(dst), false) => (vec![dst.offset, self.offset], self.offset),
+            (None, false) => (vec![self.offset], self.offset),
+        };
+
+        offsets
+            .iter()
+            .map(|offset| local - offset)
+            .find(|&utc| self.to_local(utc) == local)
+            .unwrap_or(local - before)
+    }
+
+    /// Reads a POSIX TZ string like `GMT0BST,M3.5.0/1,M10.5.0`
+    fn parse(rule: &str) -> Option<Zone> {
+        let mut rest = rule;
+        abbreviation(&mut rest)?;
+        let offset = -duration(&mut rest)?;
+        if rest.is_empty() {
+            return Some(Zone::fixed(offset));
+        }
+
+        abbreviation(&mut rest)?;
+        let dst_offset = match rest.starts_with(',') {
+            true => offset + 3600,
+            false => -duration(&mut rest)?,
+        };
+        let start = transition(&mut rest)?;
+        let end = transition(&mut rest)?;
+
+        match rest.is_empty() {
+            true => Some(Zone {
+                dst: Some(Dst {
+                    offset: dst_offset,
+                    start,
+                    end,
+                }),
+                ..Zone::fixed(offset)
+            }),
+            false => None,
+        }
+    }
+}
+
+impl Transition {
+    /// The wall clock time of the change in a year
+    fn at(&self, year: i64) -> i64 {
+        let days = match self.day {
+            Day::Weekday {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = dates::days_from_civil(year, month, 1);
+                // `weekday` counts from Sunday, `dates::weekday` from Monday
+                let first_weekday = (dates::weekday(first) + 1) % 7;
+                let mut day = (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                while day >= dates::days_in_month(year, month) {
+                    day -= 7;
+                }
+                first + day as i64
+            }
+            Day::Julian(day) => {
+                let leap_day = dates::is_leap_year(year) && day >= 60;
+                dates::days_from_civil(year, 1, 1) + day as i64 - if leap_day { 0 } else { 1 }
+            }
+            Day::Ordinal(day) => dates::days_from_civil(year, 1, 1) + day as i64,
+        };
+
+        days * SECONDS_PER_DAY + self.time
+    }
+}
+
+/// Skips a zone abbreviation like `BST` or `<+09>`
+fn abbreviation(rest: &mut &str) -> Option<()> {
+    let end = match rest.strip_prefix('<') {
+        Some(quoted) => quoted.find('>')? + 2,
+        None => rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len()),
+    };
+
+    *rest = &rest[end..];
+    (end > 0).then_some(())
+}
+
+fn number(rest: &mut &str) -> Option<i64> {
+    let end = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let number = rest[..end].parse().ok()?;
+    *rest = &rest[end..];
+    Some(number)
+}
+
+/// Reads `[+-]hh[:mm[:ss]]` as seconds
+fn duration(rest: &mut &str) -> Option<i64> {
+    let sign = match rest.chars().next()? {
+        '-' => -1,
+        _ => 1,
+    };
+    *rest = rest.trim_start_matches(['+', '-']);
+
+    let mut seconds = number(rest)? * 3600;
+    for size in [60, 1].iter() {
+        match rest.strip_prefix(':') {
+            Some(after) => *rest = after,
+            None => break,
+        }
+        seconds += number(rest)? * size;
+    }
+
+    Some(sign * seconds)
+}
+
+/// Reads `,M3.5.0/1`, `,J60` or `,59`, changing at 2:00 by default
+fn transition(rest: &mut &str) -> Option<Transition> {
+    *rest = rest.strip_prefix(',')?;
+    let day = if let Some(after) = rest.strip_prefix('M') {
+        *rest = after;
+        let month = number(rest)? as u32;
+        *rest = rest.strip_prefix('.')?;
+        let week = number(rest)? as u32;
+        *rest = rest.strip_prefix('.')?;
+        let weekday = number(rest)? as u32;
+        Day::Weekday {
+            month,
+            week,
+            weekday,
+        }
+    } else if let Some(after) = rest.strip_prefix('J') {
+        *rest = after;
+        Day::Julian(number(rest)? as u32)
+    } else {
+        Day::Ordinal(number(rest)? as u32)
+    };
+
+    let time = match rest.strip_prefix('/') {
+        Some(after) => {
+            *rest = after;
+            duration(rest)?
+        }
+        None => 7200,
+    };
+
+    Some(Transition { day, time })
+}
+
+/// The zone a name like `Europe/London`, `london`, `New_York`, `Mumbai`
+/// or `PST` stands for. Abbreviations are fixed offsets, so `PST` is
+/// UTC-8 all year round while `Los_Angeles` changes with the seasons.
+pub fn find(name: &str) -> Option<Zone> {
+    let abbreviation = ABBREVIATIONS.iter().find(|(abbreviation, _)| *abbreviation == name);
+    if let Some((_, offset)) = abbreviation {
+        return Some(Zone::fixed(*offset));
+    }
+
+    let name = name.replace(' ', "_");
+    let matches = |zone: &str| {
+        zone.eq_ignore_ascii_case(&name)
+            || zone.rsplit('/').next().is_some_and(|city| city.eq_ignore_ascii_case(&name))
+    };
+    let zone = CITIES
+        .iter()
+        .find(|(city, _)| city.eq_ignore_ascii_case(&name))
+        .map(|(_, zone)| *zone)
+        .or_else(|| ZONES.iter().map(|(zone, _)| *zone).find(|zone| matches(zone)))?;
+
+    ZONES
+        .iter()
+        .find(|(region, _)| *region == zone)
+        .and_then(|(_, rule)| Zone::parse(rule))
+}
+
+const HOUR: i64 = 3600;
+
+/// Abbreviations in common use, with their offsets from UTC. Ambiguous
+/// ones stand for the zone most people mean, e.g. `IST` for India.
+const ABBREVIATIONS: [(&str, i64); 31] = [
+    ("UTC", 0),
+    ("GMT", 0),
+    ("WET", 0),
+    ("WEST", HOUR),
+    ("BST", HOUR),
+    ("CET", HOUR),
+    ("CEST", 2 * HOUR),
+    ("EET", 2 * HOUR),
+    ("EEST", 3 * HOUR),
+    ("MSK", 3 * HOUR),
+    ("IST", 5 * HOUR + HOUR / 2),
+    ("CST", -6 * HOUR),
+    ("CDT", -5 * HOUR),
+    ("EST", -5 * HOUR),
+    ("EDT", -4 * HOUR),
+    ("MST", -7 * HOUR),
+    ("MDT", -6 * HOUR),
+    ("PST", -8 * HOUR),
+    ("PDT", -7 * HOUR),
+    ("AKST", -9 * HOUR),
+    ("AKDT", -8 * HOUR),
+    ("HST", -10 * HOUR),
+    ("AST", -4 * HOUR),
+    ("JST", 9 * HOUR),
+    ("KST", 9 * HOUR),
+    ("AWST", 8 * HOUR),
+    ("ACST", 9 * HOUR + HOUR / 2),
+    ("AEST", 10 * HOUR),
+    ("AEDT", 11 * HOUR),
+    ("NZST", 12 * HOUR),
+    ("NZDT", 13 * HOUR),
+];
+
+/// Big cities that share the zone of another one
+const CITIES: [(&str, &str); 14] = [
+    ("San_Francisco", "America/Los_Angeles"),
+    ("Seattle", "America/Los_Angeles"),
+    ("Boston", "America/New_York"),
+    ("Washington", "America/New_York"),
+    ("Miami", "America/New_York"),
+    ("Dallas", "America/Chicago"),
+    ("Beijing", "Asia/Shanghai"),
+    ("Delhi", "Asia/Kolkata"),
+    ("Mumbai", "Asia/Kolkata"),
+    ("Bangalore", "Asia/Kolkata"),
+    ("Osaka", "Asia/Tokyo"),
+    ("Munich", "Europe/Berlin"),
+    ("Barcelona", "Europe/Madrid"),
+    ("Kiev", "Europe/Kyiv"),
+];
+
+/// The current rules of every zone in the IANA time zone database, version
+/// 2025b, as POSIX TZ strings. Past changes to the rules are not kept, so
+/// dates long ago use today's offsets.
+pub const ZONES: [(&str, &str); 419] = [
+    ("Africa/Abidjan", "GMT0"),
+    ("Africa/Accra", "GMT0"),
+    ("Africa/Addis_Ababa", "EAT-3"),
+    ("Africa/Algiers", "CET-1"),
+    ("Africa/Asmara", "EAT-3"),
+    ("Africa/Bamako", "GMT0"),
+    ("Africa/Bangui", "WAT-1"),
+    ("Africa/Banjul", "GMT0"),
+    ("Africa/Bissau", "GMT0"),
+    ("Africa/Blantyre", "CAT-2"),
+    ("Africa/Brazzaville", "WAT-1"),
+    ("Africa/Bujumbura", "CAT-2"),
+    ("Africa/Cairo", "EET-2EEST,M4.5.5/0,M10.5.4/24"),
+    ("Africa/Casablanca", "<+01>-1"),
+    ("Africa/Ceuta", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Africa/Conakry", "GMT0"),
+    ("Africa/Dakar", "GMT0"),
+    ("Africa/Dar_es_Salaam", "EAT-3"),
+    ("Africa/Djibouti", "EAT-3"),
+    ("Africa/Douala", "WAT-1"),
+    ("Africa/El_Aaiun", "<+01>-1"),
+    ("Africa/Freetown", "GMT0"),
+    ("Africa/Gaborone", "CAT-2"),
+    ("Africa/Harare", "CAT-2"),
+    ("Africa/Johannesburg", "SAST-2"),
+    ("Africa/Juba", "CAT-2"),
+    ("Africa/Kampala", "EAT-3"),
+    ("Africa/Khartoum", "CAT-2"),
+    ("Africa/Kigali", "CAT-2"),
+    ("Africa/Kinshasa", "WAT-1"),
+    ("Africa/Lagos", "WAT-1"),
+    ("Africa/Libreville", "WAT-1"),
+    ("Africa/Lome", "GMT0"),
+    ("Africa/Luanda", "WAT-1"),
+    ("Africa/Lubumbashi", "CAT-2"),
+    ("Africa/Lusaka", "CAT-2"),
+    ("Africa/Malabo", "WAT-1"),
+    ("Africa/Maputo", "CAT-2"),
+    ("Africa/Maseru", "SAST-2"),
+    ("Africa/Mbabane", "SAST-2"),
+    ("Africa/Mogadishu", "EAT-3"),
+    ("Africa/Monrovia", "GMT0"),
+    ("Africa/Nairobi", "EAT-3"),
+    ("Africa/Ndjamena", "WAT-1"),
+    ("Africa/Niamey", "WAT-1"),
+    ("Africa/Nouakchott", "GMT0"),
+    ("Africa/Ouagadougou", "GMT0"),
+    ("Africa/Porto-Novo", "WAT-1"),
+    ("Africa/Sao_Tome", "GMT0"),
+    ("Africa/Tripoli", "EET-2"),
+    ("Africa/Tunis", "CET-1"),
+    ("Africa/Windhoek", "CAT-2"),
+    ("America/Adak", "HST10HDT,M3.2.0,M11.1.0"),
+    ("America/Anchorage", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("America/Anguilla", "AST4"),
+    ("America/Antigua", "AST4"),
+    ("America/Araguaina", "<-03>3"),
+    ("America/Argentina/Buenos_Aires", "<-03>3"),
+    ("America/Argentina/Catamarca", "<-03>3"),
+    ("America/Argentina/Cordoba", "<-03>3"),
+    ("America/Argentina/Jujuy", "<-03>3"),
+    ("America/Argentina/La_Rioja", "<-03>3"),
+    ("America/Argentina/Mendoza", "<-03>3"),
+    ("America/Argentina/Rio_Gallegos", "<-03>3"),
+    ("America/Argentina/Salta", "<-03>3"),
+    ("America/Argentina/San_Juan", "<-03>3"),
+    ("America/Argentina/San_Luis", "<-03>3"),
+    ("America/Argentina/Tucuman", "<-03>3"),
+    ("America/Argentina/Ushuaia", "<-03>3"),
+    ("America/Aruba", "AST4"),
+    ("America/Asuncion", "<-03>3"),
+    ("America/Atikokan", "EST5"),
+    ("America/Bahia", "<-03>3"),
+    ("America/Bahia_Banderas", "CST6"),
+    ("America/Barbados", "AST4"),
+    ("America/Belem", "<-03>3"),
+    ("America/Belize", "CST6"),
+    ("America/Blanc-Sablon", "AST4"),
+    ("America/Boa_Vista", "<-04>4"),
+    ("America/Bogota", "<-05>5"),
+    ("America/Boise", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Cambridge_Bay", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Campo_Grande", "<-04>4"),
+    ("America/Cancun", "EST5"),
+    ("America/Caracas", "<-04>4"),
+    ("America/Cayenne", "<-03>3"),
+    ("America/Cayman", "EST5"),
+    ("America/Chicago", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Chihuahua", "CST6"),
+    ("America/Ciudad_Juarez", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Costa_Rica", "CST6"),
+    ("America/Coyhaique", "<-03>3"),
+    ("America/Creston", "MST7"),
+    ("America/Cuiaba", "<-04>4"),
+    ("America/Curacao", "AST4"),
+    ("America/Danmarkshavn", "GMT0"),
+    ("America/Dawson", "MST7"),
+    ("America/Dawson_Creek", "MST7"),
+    ("America/Denver", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Detroit", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Dominica", "AST4"),
+    ("America/Edmonton", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Eirunepe", "<-05>5"),
+    ("America/El_Salvador", "CST6"),
+    ("America/Fort_Nelson", "MST7"),
+    ("America/Fortaleza", "<-03>3"),
+    ("America/Glace_Bay", "AST4ADT,M3.2.0,M11.1.0"),
+    ("America/Goose_Bay", "AST4ADT,M3.2.0,M11.1.0"),
+    ("America/Grand_Turk", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Grenada", "AST4"),
+    ("America/Guadeloupe", "AST4"),
+    ("America/Guatemala", "CST6"),
+    ("America/Guayaquil", "<-05>5"),
+    ("America/Guyana", "<-04>4"),
+    ("America/Halifax", "AST4ADT,M3.2.0,M11.1.0"),
+    ("America/Havana", "CST5CDT,M3.2.0/0,M11.1.0/1"),
+    ("America/Hermosillo", "MST7"),
+    ("America/Indiana/Indianapolis", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Knox", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Marengo", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Petersburg", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Tell_City", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Vevay", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Vincennes", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Indiana/Winamac", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Inuvik", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Iqaluit", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Jamaica", "EST5"),
+    ("America/Juneau", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("America/Kentucky/Louisville", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Kentucky/Monticello", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Kralendijk", "AST4"),
+    ("America/La_Paz", "<-04>4"),
+    ("America/Lima", "<-05>5"),
+    ("America/Los_Angeles", "PST8PDT,M3.2.0,M11.1.0"),
+    ("America/Lower_Princes", "AST4"),
+    ("America/Maceio", "<-03>3"),
+    ("America/Managua", "CST6"),
+    ("America/Manaus", "<-04>4"),
+    ("America/Marigot", "AST4"),
+    ("America/Martinique", "AST4"),
+    ("America/Matamoros", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Mazatlan", "MST7"),
+    ("America/Menominee", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Merida", "CST6"),
+    ("America/Metlakatla", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("America/Mexico_City", "CST6"),
+    ("America/Miquelon", "<-03>3<-02>,M3.2.0,M11.1.0"),
+    ("America/Moncton", "AST4ADT,M3.2.0,M11.1.0"),
+    ("America/Monterrey", "CST6"),
+    ("America/Montevideo", "<-03>3"),
+    ("America/Montserrat", "AST4"),
+    ("America/Nassau", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/New_York", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Nome", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("America/Noronha", "<-02>2"),
+    ("America/North_Dakota/Beulah", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/North_Dakota/Center", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/North_Dakota/New_Salem", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Nuuk", "<-02>2<-01>,M3.5.0/-1,M10.5.0/0"),
+    ("America/Ojinaga", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Panama", "EST5"),
+    ("America/Paramaribo", "<-03>3"),
+    ("America/Phoenix", "MST7"),
+    ("America/Port-au-Prince", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Port_of_Spain", "AST4"),
+    ("America/Porto_Velho", "<-04>4"),
+    ("America/Puerto_Rico", "AST4"),
+    ("America/Punta_Arenas", "<-03>3"),
+    ("America/Rankin_Inlet", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Recife", "<-03>3"),
+    ("America/Regina", "CST6"),
+    ("America/Resolute", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Rio_Branco", "<-05>5"),
+    ("America/Santarem", "<-03>3"),
+    ("America/Santiago", "<-04>4<-03>,M9.1.6/24,M4.1.6/24"),
+    ("America/Santo_Domingo", "AST4"),
+    ("America/Sao_Paulo", "<-03>3"),
+    ("America/Scoresbysund", "<-02>2<-01>,M3.5.0/-1,M10.5.0/0"),
+    ("America/Sitka", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("America/St_Barthelemy", "AST4"),
+    ("America/St_Johns", "NST3:30NDT,M3.2.0,M11.1.0"),
+    ("America/St_Kitts", "AST4"),
+    ("America/St_Lucia", "AST4"),
+    ("America/St_Thomas", "AST4"),
+    ("America/St_Vincent", "AST4"),
+    ("America/Swift_Current", "CST6"),
+    ("America/Tegucigalpa", "CST6"),
+    ("America/Thule", "AST4ADT,M3.2.0,M11.1.0"),
+    ("America/Tijuana", "PST8PDT,M3.2.0,M11.1.0"),
+    ("America/Toronto", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Tortola", "AST4"),
+    ("America/Vancouver", "PST8PDT,M3.2.0,M11.1.0"),
+    ("America/Whitehorse", "MST7"),
+    ("America/Winnipeg", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Yakutat", "AKST9AKDT,M3.2.0,M11.1.0"),
+    ("Antarctica/Casey", "<+08>-8"),
+    ("Antarctica/Davis", "<+07>-7"),
+    ("Antarctica/DumontDUrville", "<+10>-10"),
+    ("Antarctica/Macquarie", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Antarctica/Mawson", "<+05>-5"),
+    ("Antarctica/McMurdo", "NZST-12NZDT,M9.5.0,M4.1.0/3"),
+    ("Antarctica/Palmer", "<-03>3"),
+    ("Antarctica/Rothera", "<-03>3"),
+    ("Antarctica/Syowa", "<+03>-3"),
+    ("Antarctica/Troll", "<+00>0<+02>-2,M3.5.0/1,M10.5.0/3"),
+    ("Antarctica/Vostok", "<+05>-5"),
+    ("Arctic/Longyearbyen", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Asia/Aden", "<+03>-3"),
+    ("Asia/Almaty", "<+05>-5"),
+    ("Asia/Amman", "<+03>-3"),
+    ("Asia/Anadyr", "<+12>-12"),
+    ("Asia/Aqtau", "<+05>-5"),
+    ("Asia/Aqtobe", "<+05>-5"),
+    ("Asia/Ashgabat", "<+05>-5"),
+    ("Asia/Atyrau", "<+05>-5"),
+    ("Asia/Baghdad", "<+03>-3"),
+    ("Asia/Bahrain", "<+03>-3"),
+    ("Asia/Baku", "<+04>-4"),
+    ("Asia/Bangkok", "<+07>-7"),
+    ("Asia/Barnaul", "<+07>-7"),
+    ("Asia/Beirut", "EET-2EEST,M3.5.0/0,M10.5.0/0"),
+    ("Asia/Bishkek", "<+06>-6"),
+    ("Asia/Brunei", "<+08>-8"),
+    ("Asia/Chita", "<+09>-9"),
+    ("Asia/Colombo", "<+0530>-5:30"),
+    ("Asia/Damascus", "<+03>-3"),
+    ("Asia/Dhaka", "<+06>-6"),
+    ("Asia/Dili", "<+09>-9"),
+    ("Asia/Dubai", "<+04>-4"),
+    ("Asia/Dushanbe", "<+05>-5"),
+    ("Asia/Famagusta", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Asia/Gaza", "EET-2EEST,M3.4.4/50,M10.4.4/50"),
+    ("Asia/Hebron", "EET-2EEST,M3.4.4/50,M10.4.4/50"),
+    ("Asia/Ho_Chi_Minh", "<+07>-7"),
+    ("Asia/Hong_Kong", "HKT-8"),
+    ("Asia/Hovd", "<+07>-7"),
+    ("Asia/Irkutsk", "<+08>-8"),
+    ("Asia/Jakarta", "WIB-7"),
+    ("Asia/Jayapura", "WIT-9"),
+    ("Asia/Jerusalem", "IST-2IDT,M3.4.4/26,M10.5.0"),
+    ("Asia/Kabul", "<+0430>-4:30"),
+    ("Asia/Kamchatka", "<+12>-12"),
+    ("Asia/Karachi", "PKT-5"),
+    ("Asia/Kathmandu", "<+0545>-5:45"),
+    ("Asia/Khandyga", "<+09>-9"),
+    ("Asia/Kolkata", "IST-5:30"),
+    ("Asia/Krasnoyarsk", "<+07>-7"),
+    ("Asia/Kuala_Lumpur", "<+08>-8"),
+    ("Asia/Kuching", "<+08>-8"),
+    ("Asia/Kuwait", "<+03>-3"),
+    ("Asia/Macau", "CST-8"),
+    ("Asia/Magadan", "<+11>-11"),
+    ("Asia/Makassar", "WITA-8"),
+    ("Asia/Manila", "PST-8"),
+    ("Asia/Muscat", "<+04>-4"),
+    ("Asia/Nicosia", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Asia/Novokuznetsk", "<+07>-7"),
+    ("Asia/Novosibirsk", "<+07>-7"),
+    ("Asia/Omsk", "<+06>-6"),
+    ("Asia/Oral", "<+05>-5"),
+    ("Asia/Phnom_Penh", "<+07>-7"),
+    ("Asia/Pontianak", "WIB-7"),
+    ("Asia/Pyongyang", "KST-9"),
+    ("Asia/Qatar", "<+03>-3"),
+    ("Asia/Qostanay", "<+05>-5"),
+    ("Asia/Qyzylorda", "<+05>-5"),
+    ("Asia/Riyadh", "<+03>-3"),
+    ("Asia/Sakhalin", "<+11>-11"),
+    ("Asia/Samarkand", "<+05>-5"),
+    ("Asia/Seoul", "KST-9"),
+    ("Asia/Shanghai", "CST-8"),
+    ("Asia/Singapore", "<+08>-8"),
+    ("Asia/Srednekolymsk", "<+11>-11"),
+    ("Asia/Taipei", "CST-8"),
+    ("Asia/Tashkent", "<+05>-5"),
+    ("Asia/Tbilisi", "<+04>-4"),
+    ("Asia/Tehran", "<+0330>-3:30"),
+    ("Asia/Thimphu", "<+06>-6"),
+    ("Asia/Tokyo", "JST-9"),
+    ("Asia/Tomsk", "<+07>-7"),
+    ("Asia/Ulaanbaatar", "<+08>-8"),
+    ("Asia/Urumqi", "<+06>-6"),
+    ("Asia/Ust-Nera", "<+10>-10"),
+    ("Asia/Vientiane", "<+07>-7"),
+    ("Asia/Vladivostok", "<+10>-10"),
+    ("Asia/Yakutsk", "<+09>-9"),
+    ("Asia/Yangon", "<+0630>-6:30"),
+    ("Asia/Yekaterinburg", "<+05>-5"),
+    ("Asia/Yerevan", "<+04>-4"),
+    ("Atlantic/Azores", "<-01>1<+00>,M3.5.0/0,M10.5.0/1"),
+    ("Atlantic/Bermuda", "AST4ADT,M3.2.0,M11.1.0"),
+    ("Atlantic/Canary", "WET0WEST,M3.5.0/1,M10.5.0"),
+    ("Atlantic/Cape_Verde", "<-01>1"),
+    ("Atlantic/Faroe", "WET0WEST,M3.5.0/1,M10.5.0"),
+    ("Atlantic/Madeira", "WET0WEST,M3.5.0/1,M10.5.0"),
+    ("Atlantic/Reykjavik", "GMT0"),
+    ("Atlantic/South_Georgia", "<-02>2"),
+    ("Atlantic/St_Helena", "GMT0"),
+    ("Atlantic/Stanley", "<-03>3"),
+    ("Australia/Adelaide", "ACST-9:30ACDT,M10.1.0,M4.1.0/3"),
+    ("Australia/Brisbane", "AEST-10"),
+    ("Australia/Broken_Hill", "ACST-9:30ACDT,M10.1.0,M4.1.0/3"),
+    ("Australia/Darwin", "ACST-9:30"),
+    ("Australia/Eucla", "<+0845>-8:45"),
+    ("Australia/Hobart", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Australia/Lindeman", "AEST-10"),
+    ("Australia/Lord_Howe", "<+1030>-10:30<+11>-11,M10.1.0,M4.1.0"),
+    ("Australia/Melbourne", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Australia/Perth", "AWST-8"),
+    ("Australia/Sydney", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Europe/Amsterdam", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Andorra", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Astrakhan", "<+04>-4"),
+    ("Europe/Athens", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Belgrade", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Berlin", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Bratislava", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Brussels", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Bucharest", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Budapest", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Busingen", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Chisinau", "EET-2EEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Copenhagen", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Dublin", "IST-1GMT0,M10.5.0,M3.5.0/1"),
+    ("Europe/Gibraltar", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Guernsey", "GMT0BST,M3.5.0/1,M10.5.0"),
+    ("Europe/Helsinki", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Isle_of_Man", "GMT0BST,M3.5.0/1,M10.5.0"),
+    ("Europe/Istanbul", "<+03>-3"),
+    ("Europe/Jersey", "GMT0BST,M3.5.0/1,M10.5.0"),
+    ("Europe/Kaliningrad", "EET-2"),
+    ("Europe/Kirov", "MSK-3"),
+    ("Europe/Kyiv", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Lisbon", "WET0WEST,M3.5.0/1,M10.5.0"),
+    ("Europe/Ljubljana", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/London", "GMT0BST,M3.5.0/1,M10.5.0"),
+    ("Europe/Luxembourg", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Madrid", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Malta", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Mariehamn", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Minsk", "<+03>-3"),
+    ("Europe/Monaco", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Moscow", "MSK-3"),
+    ("Europe/Oslo", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Paris", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Podgorica", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Prague", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Riga", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Rome", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Samara", "<+04>-4"),
+    ("Europe/San_Marino", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Sarajevo", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Saratov", "<+04>-4"),
+    ("Europe/Simferopol", "MSK-3"),
+    ("Europe/Skopje", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Sofia", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Stockholm", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Tallinn", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Tirane", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Ulyanovsk", "<+04>-4"),
+    ("Europe/Vaduz", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Vatican", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Vienna", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Vilnius", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Volgograd", "MSK-3"),
+    ("Europe/Warsaw", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Zagreb", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Zurich", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Indian/Antananarivo", "EAT-3"),
+    ("Indian/Chagos", "<+06>-6"),
+    ("Indian/Christmas", "<+07>-7"),
+    ("Indian/Cocos", "<+0630>-6:30"),
+    ("Indian/Comoro", "EAT-3"),
+    ("Indian/Kerguelen", "<+05>-5"),
+    ("Indian/Mahe", "<+04>-4"),
+    ("Indian/Maldives", "<+05>-5"),
+    ("Indian/Mauritius", "<+04>-4"),
+    ("Indian/Mayotte", "EAT-3"),
+    ("Indian/Reunion", "<+04>-4"),
+    ("Pacific/Apia", "<+13>-13"),
+    ("Pacific/Auckland", "NZST-12NZDT,M9.5.0,M4.1.0/3"),
+    ("Pacific/Bougainville", "<+11>-11"),
+    ("Pacific/Chatham", "<+1245>-12:45<+1345>,M9.5.0/2:45,M4.1.0/3:45"),
+    ("Pacific/Chuuk", "<+10>-10"),
+    ("Pacific/Easter", "<-06>6<-05>,M9.1.6/22,M4.1.6/22"),
+    ("Pacific/Efate", "<+11>-11"),
+    ("Pacific/Fakaofo", "<+13>-13"),
+    ("Pacific/Fiji", "<+12>-12"),
+    ("Pacific/Funafuti", "<+12>-12"),
+    ("Pacific/Galapagos", "<-06>6"),
+    ("Pacific/Gambier", "<-09>9"),
+    ("Pacific/Guadalcanal", "<+11>-11"),
+    ("Pacific/Guam", "ChST-10"),
+    ("Pacific/Honolulu", "HST10"),
+    ("Pacific/Kanton", "<+13>-13"),
+    ("Pacific/Kiritimati", "<+14>-14"),
+    ("Pacific/Kosrae", "<+11>-11"),
+    ("Pacific/Kwajalein", "<+12>-12"),
+    ("Pacific/Majuro", "<+12>-12"),
+    ("Pacific/Marquesas", "<-0930>9:30"),
+    ("Pacific/Midway", "SST11"),
+    ("Pacific/Nauru", "<+12>-12"),
+    ("Pacific/Niue", "<-11>11"),
+    ("Pacific/Norfolk", "<+11>-11<+12>,M10.1.0,M4.1.0/3"),
+    ("Pacific/Noumea", "<+11>-11"),
+    ("Pacific/Pago_Pago", "SST11"),
+    ("Pacific/Palau", "<+09>-9"),
+    ("Pacific/Pitcairn", "<-08>8"),
+    ("Pacific/Pohnpei", "<+11>-11"),
+    ("Pacific/Port_Moresby", "<+10>-10"),
+    ("Pacific/Rarotonga", "<-10>10"),
+    ("Pacific/Saipan", "ChST-10"),
+    ("Pacific/Tahiti", "<-10>10"),
+    ("Pacific/Tarawa", "<+12>-12"),
+    ("Pacific/Tongatapu", "<+13>-13"),
+    ("Pacific/Wake", "<+12>-12"),
+    ("Pacific/Wallis", "<+12>-12"),
+    ("UTC", "UTC0"),
+];